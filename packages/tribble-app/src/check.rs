@@ -0,0 +1,1136 @@
+use crate::condition::Condition;
+use crate::diagnostics::fmt_key;
+use crate::errors::ParserError;
+use crate::form::FormValue;
+use crate::option_files::resolve_option_files;
+use crate::parser::{
+    Config, Endpoint, Input, InputSectionElem, InputType, Section, SectionElem, Workflow,
+};
+use crate::template::{Template, BUILTIN_VARS};
+use crate::validation::{validate, TagMatch};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A single problem found while statically checking a configuration. Problems don't stop the check, so we can report all of them at once.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    /// The file the problem was found in.
+    pub filename: String,
+    /// The path to the offending element within that file (e.g. `workflows.test.sections."Report Bug"[3].link`).
+    pub path: String,
+    /// A description of what's wrong.
+    pub msg: String,
+}
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.filename, self.path, self.msg)
+    }
+}
+
+/// Checks the configuration at the given path (and all its language files if it's a root file) for any structural problems that would
+/// otherwise only be discovered at runtime (e.g. dangling links). Parsing errors are still returned as errors, because we can't check
+/// anything in a file we can't read.
+pub fn check_config(root_cfg_path: &str) -> Result<Vec<Problem>, ParserError> {
+    let root_cfg = Config::new(root_cfg_path)?;
+    let mut problems = Vec::new();
+    match root_cfg {
+        Config::Root { ref languages, .. } => {
+            let reference_locale = match root_cfg.reference_locale() {
+                Some(locale) => locale,
+                None => {
+                    return Err(ParserError::NoLanguages {
+                        filename: root_cfg_path.to_string(),
+                    })
+                }
+            };
+            if !languages.contains_key(reference_locale) {
+                return Err(ParserError::NoSuchReferenceLocale {
+                    filename: root_cfg_path.to_string(),
+                    locale: reference_locale.to_string(),
+                });
+            }
+            // We sort the languages so that the output is deterministic
+            let mut lang_cfgs = Vec::new();
+            for (locale, lang_cfg_path) in sorted(languages) {
+                match Config::new(lang_cfg_path)? {
+                    Config::Language { mut workflows, .. } => {
+                        for workflow in workflows.values_mut() {
+                            resolve_option_files(lang_cfg_path, workflow)?;
+                        }
+                        check_workflows(lang_cfg_path, &workflows, &mut problems);
+                        lang_cfgs.push(LangCfg {
+                            locale,
+                            filename: lang_cfg_path,
+                            workflows,
+                        });
+                    }
+                    Config::Root { .. } => {
+                        return Err(ParserError::RootLinksToRoot {
+                            filename: root_cfg_path.to_string(),
+                            linked: lang_cfg_path.to_string(),
+                        })
+                    }
+                }
+            }
+            // Now make sure every translation has the same structure as the reference locale
+            let reference = lang_cfgs
+                .iter()
+                .find(|cfg| cfg.locale == reference_locale)
+                .unwrap(); // We checked that this locale exists above
+            for lang_cfg in lang_cfgs
+                .iter()
+                .filter(|cfg| cfg.locale != reference_locale)
+            {
+                check_consistency(reference, lang_cfg, &mut problems);
+            }
+        }
+        Config::Language { mut workflows, .. } => {
+            for workflow in workflows.values_mut() {
+                resolve_option_files(root_cfg_path, workflow)?;
+            }
+            check_workflows(root_cfg_path, &workflows, &mut problems)
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Checks every workflow in a single language file, adding any problems found to the given list.
+fn check_workflows(
+    filename: &str,
+    workflows: &HashMap<String, Workflow>,
+    problems: &mut Vec<Problem>,
+) {
+    let mut problem = |path: String, msg: String| {
+        problems.push(Problem {
+            filename: filename.to_string(),
+            path,
+            msg,
+        })
+    };
+
+    for (workflow_name, workflow) in sorted(workflows) {
+        let workflow_path = format!("workflows.{}", fmt_key(workflow_name));
+        if let Some(msg) = check_link(&workflow.index, workflow) {
+            problem(format!("{}.index", workflow_path), msg);
+        }
+
+        // Input IDs are global to a workflow, so we keep track of where we first saw each one
+        let mut input_ids: HashMap<&str, String> = HashMap::new();
+        // Conditions can reference inputs anywhere in the workflow, so we check them once we've seen every input (conditions on inputs in
+        // groups can reference the other inputs in the same entry too, so we keep track of those)
+        let mut conditions: Vec<(String, &Condition, &[InputSectionElem])> = Vec::new();
+        for (section_name, section) in sorted(&workflow.sections) {
+            let section_path = format!("{}.sections.{}", workflow_path, fmt_key(section_name));
+            for (i, elem) in section.iter().enumerate() {
+                let elem_path = format!("{}[{}]", section_path, i);
+                if let Some(when) = elem.when() {
+                    conditions.push((format!("{}.when", elem_path), when, &[]));
+                }
+                match elem {
+                    SectionElem::Progression { link, .. } => {
+                        if let Some(msg) = check_link(link, workflow) {
+                            problem(format!("{}.link", elem_path), msg);
+                        }
+                    }
+                    SectionElem::Redirect { redirect, tags, .. } => {
+                        if let Some(msg) = check_link(redirect, workflow) {
+                            problem(format!("{}.redirect", elem_path), msg);
+                        }
+                        if section_name == workflow.index && !tags.is_empty() {
+                            problem(
+                                format!("{}.tags", elem_path),
+                                "redirects in the index section can't accumulate tags".to_string(),
+                            );
+                        }
+                    }
+                    SectionElem::Input(input) => match input_ids.get(input.id.as_str()) {
+                        Some(first_path) => problem(
+                            format!("{}.id", elem_path),
+                            format!(
+                                "duplicate input id '{}' (already defined at {})",
+                                input.id, first_path
+                            ),
+                        ),
+                        None => {
+                            if input.id.starts_with('_') {
+                                problem(format!("{}.id", elem_path), reserved_msg(&input.id));
+                            }
+                            if let Some(msg) = check_default(input) {
+                                problem(
+                                    format!("{}.default", elem_path),
+                                    format!(
+                                        "default value breaks the input's own validation rules ({})",
+                                        msg
+                                    ),
+                                );
+                            }
+                            if let Some((j, msg)) = check_tag_rules(input) {
+                                problem(format!("{}.tag_rules[{}]", elem_path, j), msg);
+                            }
+                            input_ids.insert(&input.id, elem_path);
+                        }
+                    },
+                    SectionElem::Group(group) => {
+                        match input_ids.get(group.group.as_str()) {
+                            Some(first_path) => problem(
+                                format!("{}.group", elem_path),
+                                format!(
+                                    "duplicate input id '{}' (already defined at {})",
+                                    group.group, first_path
+                                ),
+                            ),
+                            None => {
+                                if group.group.starts_with('_') {
+                                    problem(
+                                        format!("{}.group", elem_path),
+                                        reserved_msg(&group.group),
+                                    );
+                                }
+                                input_ids.insert(&group.group, elem_path.clone());
+                            }
+                        }
+                        if group.max.is_some_and(|max| max < group.min) {
+                            problem(
+                                format!("{}.max", elem_path),
+                                format!(
+                                    "group allows at most {} entries, but it has to have at least {}",
+                                    group.max.unwrap(),
+                                    group.min
+                                ),
+                            );
+                        }
+                        // The inputs in a group only need unique IDs within that group
+                        let mut group_ids: HashMap<&str, String> = HashMap::new();
+                        for (j, input) in group.inputs.iter().enumerate() {
+                            let input_path = format!("{}.inputs[{}]", elem_path, j);
+                            if let Some(when) = &input.when {
+                                conditions.push((
+                                    format!("{}.when", input_path),
+                                    when,
+                                    &group.inputs,
+                                ));
+                            }
+                            match group_ids.get(input.id.as_str()) {
+                                Some(first_path) => problem(
+                                    format!("{}.id", input_path),
+                                    format!(
+                                        "duplicate input id '{}' in group '{}' (already defined at {})",
+                                        input.id, group.group, first_path
+                                    ),
+                                ),
+                                None => {
+                                    if let Some(msg) = check_default(input) {
+                                        problem(
+                                            format!("{}.default", input_path),
+                                            format!(
+                                                "default value breaks the input's own validation rules ({})",
+                                                msg
+                                            ),
+                                        );
+                                    }
+                                    if let Some((k, msg)) = check_tag_rules(input) {
+                                        problem(format!("{}.tag_rules[{}]", input_path, k), msg);
+                                    }
+                                    group_ids.insert(&input.id, input_path);
+                                }
+                            }
+                        }
+                    }
+                    SectionElem::Text(_) | SectionElem::ConditionalText { .. } => (),
+                }
+            }
+        }
+        // A section that nothing leads to will never be shown (links to a step only go where the user could have got to themselves)
+        let mut starts = vec![workflow.index.as_str()];
+        starts.extend(
+            workflow
+                .prefill
+                .sections
+                .iter()
+                .map(|section| section.as_str()),
+        );
+        let reachable = reachable_sections(workflow, starts);
+        for (section_name, _) in sorted(&workflow.sections) {
+            if !reachable.contains(section_name) {
+                problem(
+                    format!("{}.sections.{}", workflow_path, fmt_key(section_name)),
+                    "section can't be reached from the index (or from any section in `prefill.sections`)"
+                        .to_string(),
+                );
+            }
+        }
+        // Only top-level inputs can be prefilled, because there's no way to say which entry of a group a value should go in
+        for (i, id) in workflow.prefill.inputs.iter().enumerate() {
+            let is_input = workflow
+                .sections
+                .values()
+                .flatten()
+                .any(|elem| matches!(elem, SectionElem::Input(input) if &input.id == id));
+            if !is_input {
+                problem(
+                    format!("{}.prefill.inputs[{}]", workflow_path, i),
+                    format!("can't prefill '{}', because no input has that id", id),
+                );
+            }
+        }
+        for (i, section) in workflow.prefill.sections.iter().enumerate() {
+            if !workflow.sections.contains_key(section) {
+                problem(
+                    format!("{}.prefill.sections[{}]", workflow_path, i),
+                    format!("can't start at nonexistent section '{}'", section),
+                );
+            }
+        }
+        // Fields are filled in from inputs and the built-in values, never from other fields (so there's no order to worry about)
+        for (name, template) in sorted(&workflow.fields) {
+            let field_path = format!("{}.fields.{}", workflow_path, fmt_key(name));
+            if name.starts_with('_') {
+                problem(field_path.clone(), reserved_msg(name));
+            } else if let Some(input_path) = input_ids.get(name) {
+                problem(
+                    field_path.clone(),
+                    format!(
+                        "field '{}' has the same name as an input (defined at {})",
+                        name, input_path
+                    ),
+                );
+            }
+            for id in template.vars() {
+                if !input_ids.contains_key(id) && !BUILTIN_VARS.contains(&id) {
+                    let msg = if workflow.fields.contains_key(id) {
+                        format!(
+                            "reference to '${{{}}}', but fields can't reference other fields",
+                            id
+                        )
+                    } else {
+                        format!("reference to '${{{}}}', but no input has that id", id)
+                    };
+                    problem(field_path.clone(), msg);
+                }
+            }
+        }
+        for (path, when, siblings) in conditions {
+            for id in when.vars() {
+                if !input_ids.contains_key(id) && !siblings.iter().any(|input| input.id == id) {
+                    problem(
+                        path.clone(),
+                        format!(
+                            "condition references '${{{}}}', but no input has that id",
+                            id
+                        ),
+                    );
+                }
+            }
+        }
+
+        for (endpoint_name, endpoint) in sorted(&workflow.endpoints) {
+            let endpoint_path = format!("{}.endpoints.{}", workflow_path, fmt_key(endpoint_name));
+            if let Endpoint::Report {
+                github,
+                gitlab,
+                gitea,
+                ..
+            } = endpoint
+            {
+                // Only one structured destination can be used, so the others would be silently ignored
+                let dests = [
+                    github.as_ref().map(|_| "github"),
+                    gitlab.as_ref().map(|_| "gitlab"),
+                    gitea.as_ref().map(|_| "gitea"),
+                ];
+                let dests: Vec<&str> = dests.iter().flatten().copied().collect();
+                if dests.len() > 1 {
+                    problem(
+                        endpoint_path.clone(),
+                        format!(
+                            "only one issue destination can be used, but found {}",
+                            dests.join(", ")
+                        ),
+                    );
+                }
+            }
+            // Templates can only use the values of inputs the user could have filled out on their way to this endpoint
+            let reachable = reachable_inputs(workflow, endpoint_name);
+            for (field, template) in report_templates(endpoint) {
+                for id in template.vars() {
+                    if is_computed(workflow, id) {
+                        continue;
+                    }
+                    if !input_ids.contains_key(id) {
+                        problem(
+                            format!("{}.{}", endpoint_path, field),
+                            format!("reference to '${{{}}}', but no input has that id", id),
+                        );
+                    } else if !reachable.contains(id) {
+                        problem(
+                            format!("{}.{}", endpoint_path, field),
+                            format!(
+                                "reference to '${{{}}}', but that input can't be reached before this endpoint",
+                                id
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Checks that every template in the given workflow's report endpoints only references inputs that can be reached before that endpoint.
+/// This is run at build time, so it stops at the first problem (`tribble check` will list all of them).
+pub fn check_template_refs(
+    filename: &str,
+    workflow_name: &str,
+    workflow: &Workflow,
+) -> Result<(), ParserError> {
+    for (endpoint_name, endpoint) in sorted(&workflow.endpoints) {
+        let reachable = reachable_inputs(workflow, endpoint_name);
+        for (field, template) in report_templates(endpoint) {
+            if let Some(id) = template
+                .vars()
+                .into_iter()
+                .find(|id| !reachable.contains(id) && !is_computed(workflow, id))
+            {
+                return Err(ParserError::UnreachableTemplateRef {
+                    filename: filename.to_string(),
+                    workflow: workflow_name.to_string(),
+                    endpoint: endpoint_name.to_string(),
+                    field: field.to_string(),
+                    id: id.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the default value of every input in the given workflow follows that input's validation rules. This is run at build time, so
+/// it stops at the first problem.
+pub fn check_defaults(
+    filename: &str,
+    workflow_name: &str,
+    workflow: &Workflow,
+) -> Result<(), ParserError> {
+    for (_, (_, input)) in sorted(&inputs(workflow)) {
+        if let Some(msg) = check_default(input) {
+            return Err(ParserError::InvalidDefault {
+                filename: filename.to_string(),
+                workflow: workflow_name.to_string(),
+                input: input.id.to_string(),
+                msg: msg.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks an input's default value against its validation rules, returning the message of the first rule it breaks. Empty defaults aren't
+/// checked, just like empty values.
+fn check_default(input: &InputSectionElem) -> Option<&str> {
+    let value = FormValue::from_default(&input.input, input.default.as_ref()?);
+    if value.is_empty() {
+        None
+    } else {
+        validate(&input.validate, &value.to_string())
+    }
+}
+
+/// Checks an input's tag rules, returning the index of the first one that could never match along with the reason why.
+fn check_tag_rules(input: &InputSectionElem) -> Option<(usize, String)> {
+    let is_number = matches!(
+        &input.input,
+        Input::Text {
+            input_type: InputType::Number { .. } | InputType::Range { .. }
+        }
+    );
+    input
+        .tag_rules
+        .iter()
+        .enumerate()
+        .find_map(|(i, rule)| match rule.rule {
+            TagMatch::Range { .. } if !is_number => Some((
+                i,
+                format!(
+                    "{} can't be used here, because ranges only work on number and range inputs",
+                    rule.rule
+                ),
+            )),
+            TagMatch::Range {
+                min: Some(min),
+                max: Some(max),
+            } if max < min => Some((
+                i,
+                format!(
+                    "{} can never match, because its max is less than its min",
+                    rule.rule
+                ),
+            )),
+            _ => None,
+        })
+}
+
+/// Gets the IDs of all the inputs the user could have filled out by the time they reach the given endpoint, which are the inputs in any
+/// section on a path from the index to the endpoint.
+pub fn reachable_inputs<'a>(workflow: &'a Workflow, endpoint: &str) -> HashSet<&'a str> {
+    // Find every section that can be reached from the index
+    let from_index = reachable_sections(workflow, vec![workflow.index.as_str()]);
+    // Then work backwards from the endpoint to find which of those lead to it
+    let target = format!("endpoint:{}", endpoint);
+    let mut to_endpoint: HashSet<&str> = HashSet::new();
+    let mut stack = vec![target.as_str()];
+    while let Some(link) = stack.pop() {
+        for name in &from_index {
+            if to_endpoint.contains(name) {
+                continue;
+            }
+            let section = &workflow.sections[*name];
+            if progressions(section).iter().any(|(_, l, _)| *l == link) {
+                to_endpoint.insert(*name);
+                stack.push(*name);
+            }
+        }
+    }
+
+    to_endpoint
+        .iter()
+        .flat_map(|name| workflow.sections[*name].iter())
+        .filter_map(|elem| match elem {
+            SectionElem::Input(input) => Some(input.id.as_str()),
+            SectionElem::Group(group) => Some(group.group.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Gets the names of all the sections that can be reached from the given sections (including those sections themselves, if they exist).
+fn reachable_sections<'a>(workflow: &'a Workflow, starts: Vec<&'a str>) -> HashSet<&'a str> {
+    let mut reachable: HashSet<&str> = HashSet::new();
+    let mut stack = starts;
+    while let Some(name) = stack.pop() {
+        if let Some((name, section)) = workflow.sections.get_key_value(name) {
+            if reachable.insert(name) {
+                stack.extend(progressions(section).into_iter().map(|(_, link, _)| link));
+            }
+        }
+    }
+
+    reachable
+}
+
+/// Checks whether the given template variable is a computed field or a built-in value, which are available everywhere (unlike inputs, which
+/// have to be reached first).
+fn is_computed(workflow: &Workflow, id: &str) -> bool {
+    workflow.fields.contains_key(id) || BUILTIN_VARS.contains(&id)
+}
+
+/// The error message for an input ID or field name that could clash with a built-in value.
+fn reserved_msg(id: &str) -> String {
+    format!(
+        "'{}' starts with an underscore, which is reserved for built-in values (like '${{_locale}}')",
+        id
+    )
+}
+
+/// Gets the templates in an endpoint that can reference inputs, along with the names of the fields they're in.
+fn report_templates(endpoint: &Endpoint) -> Vec<(&'static str, &Template)> {
+    let mut templates = Vec::new();
+    if let Endpoint::Report {
+        preamble,
+        text,
+        dest_url,
+        github,
+        gitlab,
+        gitea,
+        ..
+    } = endpoint
+    {
+        templates.push(("preamble", preamble));
+        templates.push(("text", text));
+        if let Some(dest_url) = dest_url {
+            templates.push(("dest_url", dest_url));
+        }
+        if let Some(title) = github.as_ref().and_then(|github| github.title.as_ref()) {
+            templates.push(("github.title", title));
+        }
+        if let Some(title) = gitlab.as_ref().and_then(|gitlab| gitlab.title.as_ref()) {
+            templates.push(("gitlab.title", title));
+        }
+        if let Some(title) = gitea.as_ref().and_then(|gitea| gitea.title.as_ref()) {
+            templates.push(("gitea.title", title));
+        }
+    }
+
+    templates
+}
+
+/// A parsed language configuration file, used for comparing translations.
+struct LangCfg<'a> {
+    locale: &'a str,
+    filename: &'a str,
+    workflows: HashMap<String, Workflow>,
+}
+
+/// Checks that the given translation has exactly the same structure as the reference locale (the same workflows, sections, endpoints, and
+/// inputs, with the same links and tags). Only the text should differ between translations.
+fn check_consistency(reference: &LangCfg, lang_cfg: &LangCfg, problems: &mut Vec<Problem>) {
+    let mut problem = |path: String, msg: String| {
+        problems.push(Problem {
+            filename: lang_cfg.filename.to_string(),
+            path,
+            msg,
+        })
+    };
+    let ref_locale = reference.locale;
+
+    check_keys(
+        &reference.workflows,
+        &lang_cfg.workflows,
+        "workflow",
+        ref_locale,
+        "workflows",
+        &mut problem,
+    );
+    for (workflow_name, ref_workflow) in sorted(&reference.workflows) {
+        let workflow = match lang_cfg.workflows.get(workflow_name) {
+            Some(workflow) => workflow,
+            // We've already reported this
+            None => continue,
+        };
+        let workflow_path = format!("workflows.{}", fmt_key(workflow_name));
+        if workflow.index != ref_workflow.index {
+            problem(
+                format!("{}.index", workflow_path),
+                format!(
+                    "index is '{}', but it's '{}' in the reference locale '{}'",
+                    workflow.index, ref_workflow.index, ref_locale
+                ),
+            );
+        }
+        // Descriptions are translated, but workflows should be listed the same way in every language
+        if workflow.order != ref_workflow.order {
+            problem(
+                format!("{}.order", workflow_path),
+                format!(
+                    "order is {:?}, but it's {:?} in the reference locale '{}'",
+                    workflow.order, ref_workflow.order, ref_locale
+                ),
+            );
+        }
+        if workflow.prefill != ref_workflow.prefill {
+            problem(
+                format!("{}.prefill", workflow_path),
+                format!(
+                    "prefill is {:?}, but it's {:?} in the reference locale '{}'",
+                    workflow.prefill, ref_workflow.prefill, ref_locale
+                ),
+            );
+        }
+        check_keys(
+            &ref_workflow.sections,
+            &workflow.sections,
+            "section",
+            ref_locale,
+            &format!("{}.sections", workflow_path),
+            &mut problem,
+        );
+        check_keys(
+            &ref_workflow.endpoints,
+            &workflow.endpoints,
+            "endpoint",
+            ref_locale,
+            &format!("{}.endpoints", workflow_path),
+            &mut problem,
+        );
+        check_keys(
+            &ref_workflow.fields,
+            &workflow.fields,
+            "field",
+            ref_locale,
+            &format!("{}.fields", workflow_path),
+            &mut problem,
+        );
+
+        // Progressions are compared in order within each section
+        for (section_name, ref_section) in sorted(&ref_workflow.sections) {
+            let section = match workflow.sections.get(section_name) {
+                Some(section) => section,
+                None => continue,
+            };
+            let section_path = format!("{}.sections.{}", workflow_path, fmt_key(section_name));
+            let ref_progressions = progressions(ref_section);
+            let progressions = progressions(section);
+            if progressions.len() != ref_progressions.len() {
+                problem(
+                    section_path.clone(),
+                    format!(
+                        "section has {} progression(s), but it has {} in the reference locale '{}'",
+                        progressions.len(),
+                        ref_progressions.len(),
+                        ref_locale
+                    ),
+                );
+            }
+            for ((i, link, tags), (_, ref_link, ref_tags)) in
+                progressions.iter().zip(ref_progressions.iter())
+            {
+                if link != ref_link {
+                    problem(
+                        format!("{}[{}].link", section_path, i),
+                        format!(
+                            "progression links to '{}', but the matching progression in the reference locale '{}' links to '{}'",
+                            link, ref_locale, ref_link
+                        ),
+                    );
+                }
+                if tags != ref_tags {
+                    problem(
+                        format!("{}[{}].tags", section_path, i),
+                        format!(
+                            "progression has tags {:?}, but the matching progression in the reference locale '{}' has tags {:?}",
+                            tags, ref_locale, ref_tags
+                        ),
+                    );
+                }
+            }
+        }
+
+        // Inputs are compared by their IDs, because they can be referenced from anywhere in the workflow
+        let ref_inputs = inputs(ref_workflow);
+        let inputs = inputs(workflow);
+        check_keys(
+            &ref_inputs,
+            &inputs,
+            "input",
+            ref_locale,
+            &workflow_path,
+            &mut problem,
+        );
+        for (id, (path, input)) in sorted(&inputs) {
+            if let Some((_, ref_input)) = ref_inputs.get(id) {
+                let tags = input_tags(input);
+                let ref_tags = input_tags(ref_input);
+                if tags != ref_tags {
+                    problem(
+                        path.to_string(),
+                        format!(
+                            "input '{}' accumulates tags {:?}, but it accumulates {:?} in the reference locale '{}'",
+                            id, tags, ref_tags, ref_locale
+                        ),
+                    );
+                }
+                // A translation shouldn't be able to leak values that the reference locale keeps out of links
+                if input.is_sensitive() != ref_input.is_sensitive() {
+                    problem(
+                        format!("{}.sensitive", path),
+                        format!(
+                            "input '{}' is {}sensitive, but it's {}sensitive in the reference locale '{}'",
+                            id,
+                            if input.is_sensitive() { "" } else { "not " },
+                            if ref_input.is_sensitive() { "" } else { "not " },
+                            ref_locale
+                        ),
+                    );
+                }
+                // Only the messages of validation rules should be translated
+                let rules: Vec<String> =
+                    input.validate.iter().map(|v| v.rule.to_string()).collect();
+                let ref_rules: Vec<String> = ref_input
+                    .validate
+                    .iter()
+                    .map(|v| v.rule.to_string())
+                    .collect();
+                if rules != ref_rules {
+                    problem(
+                        format!("{}.validate", path),
+                        format!(
+                            "input '{}' has validation rules {:?}, but it has {:?} in the reference locale '{}'",
+                            id, rules, ref_rules, ref_locale
+                        ),
+                    );
+                }
+                // The tags of tag rules have already been compared, but what they match has to be the same too
+                let tag_rules: Vec<String> =
+                    input.tag_rules.iter().map(|r| r.rule.to_string()).collect();
+                let ref_tag_rules: Vec<String> = ref_input
+                    .tag_rules
+                    .iter()
+                    .map(|r| r.rule.to_string())
+                    .collect();
+                if tag_rules != ref_tag_rules {
+                    problem(
+                        format!("{}.tag_rules", path),
+                        format!(
+                            "input '{}' has tag rules {:?}, but it has {:?} in the reference locale '{}'",
+                            id, tag_rules, ref_tag_rules, ref_locale
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Reports any keys that are in the reference map but not the translated one, or vice versa.
+fn check_keys<V, W>(
+    reference: &HashMap<String, V>,
+    translated: &HashMap<String, W>,
+    kind: &str,
+    ref_locale: &str,
+    path: &str,
+    problem: &mut impl FnMut(String, String),
+) {
+    for (key, _) in sorted(reference) {
+        if !translated.contains_key(key) {
+            problem(
+                path.to_string(),
+                format!(
+                    "missing {} '{}', which is in the reference locale '{}'",
+                    kind, key, ref_locale
+                ),
+            );
+        }
+    }
+    for (key, _) in sorted(translated) {
+        if !reference.contains_key(key) {
+            problem(
+                path.to_string(),
+                format!(
+                    "extra {} '{}', which isn't in the reference locale '{}'",
+                    kind, key, ref_locale
+                ),
+            );
+        }
+    }
+}
+
+/// Gets the indices, links, and tags of all the progressions (including redirects) in a section, in order.
+fn progressions(section: &Section) -> Vec<(usize, &str, &[String])> {
+    section
+        .iter()
+        .enumerate()
+        .filter_map(|(i, elem)| match elem {
+            SectionElem::Progression { link, tags, .. }
+            | SectionElem::Redirect {
+                redirect: link,
+                tags,
+                ..
+            } => Some((i, link.as_str(), tags.as_slice())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Gets all the inputs in a workflow, indexed by their IDs, along with their paths. Inputs in groups are indexed as `<group id>.<input id>`.
+fn inputs(workflow: &Workflow) -> HashMap<String, (String, &InputSectionElem)> {
+    let mut inputs = HashMap::new();
+    for (section_name, section) in &workflow.sections {
+        for (i, elem) in section.iter().enumerate() {
+            let path = format!("sections.{}[{}]", fmt_key(section_name), i);
+            match elem {
+                SectionElem::Input(input) => {
+                    inputs.entry(input.id.clone()).or_insert((path, input));
+                }
+                SectionElem::Group(group) => {
+                    for (j, input) in group.inputs.iter().enumerate() {
+                        inputs
+                            .entry(format!("{}.{}", group.group, input.id))
+                            .or_insert((format!("{}.inputs[{}]", path, j), input));
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    inputs
+}
+
+/// Gets the tags an input can accumulate, in order (for select inputs, there's one list per option).
+fn input_tags(input: &InputSectionElem) -> Vec<Vec<String>> {
+    let mut tags = match &input.input {
+        Input::Text {
+            input_type: InputType::Boolean { tags },
+        } => vec![tags.clone().unwrap_or_default()],
+        Input::Text { .. } => Vec::new(),
+        input => input
+            .options()
+            .unwrap_or_default()
+            .iter()
+            .map(|opt| opt.tags().to_vec())
+            .collect(),
+    };
+    tags.extend(input.tag_rules.iter().map(|rule| rule.tags.clone()));
+    tags
+}
+
+/// Checks that the given link (which may be prefixed with `endpoint:`) points to something that exists in the workflow, returning a
+/// description of the problem if it doesn't.
+fn check_link(link: &str, workflow: &Workflow) -> Option<String> {
+    match link.strip_prefix("endpoint:") {
+        Some(endpoint) if !workflow.endpoints.contains_key(endpoint) => {
+            Some(format!("link to nonexistent endpoint '{}'", endpoint))
+        }
+        None if !workflow.sections.contains_key(link) => {
+            Some(format!("link to nonexistent section '{}'", link))
+        }
+        _ => None,
+    }
+}
+
+/// Gets the entries of a map sorted by key, so that problems are always reported in the same order.
+fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&str, &V)> {
+    let mut entries: Vec<(&str, &V)> = map.iter().map(|(k, v)| (k.as_str(), v)).collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflows(yaml: &str) -> HashMap<String, Workflow> {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+    /// Checks the given workflows, returning each problem as `<path>: <message>`.
+    fn problems(yaml: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+        check_workflows("test.yml", &workflows(yaml), &mut problems);
+        problems
+            .iter()
+            .map(|problem| format!("{}: {}", problem.path, problem.msg))
+            .collect()
+    }
+
+    const VALID: &str = r#"
+test:
+  title: "Test"
+  index: "Start"
+  sections:
+    Start:
+      - { id: "name", label: "Name", type: "text" }
+      - { text: "Next", link: "Details", tags: [] }
+    Details:
+      - { id: "details", label: "Details", type: "multiline", when: "${name}" }
+      - { text: "Done", link: "endpoint:Done", tags: [] }
+  endpoints:
+    Done: "Thanks!"
+"#;
+
+    #[test]
+    fn valid_workflow_has_no_problems() {
+        assert_eq!(problems(VALID), Vec::<String>::new());
+    }
+    #[test]
+    fn dangling_links_are_caught() {
+        let yaml = VALID.replace(r#"link: "Details""#, r#"link: "Detials""#);
+        assert_eq!(
+            problems(&yaml),
+            vec![
+                "workflows.test.sections.Start[1].link: link to nonexistent section 'Detials'",
+                "workflows.test.sections.Details: section can't be reached from the index (or from any section in `prefill.sections`)",
+            ]
+        );
+        let yaml = VALID.replace("endpoint:Done", "endpoint:Finished");
+        assert_eq!(
+            problems(&yaml),
+            vec![
+                "workflows.test.sections.Details[1].link: link to nonexistent endpoint 'Finished'"
+            ]
+        );
+    }
+    #[test]
+    fn bad_index_is_caught() {
+        let yaml = VALID.replace(r#"index: "Start""#, r#"index: "Begin""#);
+        let problems = problems(&yaml);
+        assert_eq!(
+            problems[0],
+            "workflows.test.index: link to nonexistent section 'Begin'"
+        );
+        // Nothing can be reached without an index
+        assert_eq!(problems.len(), 3);
+    }
+    #[test]
+    fn duplicate_input_ids_are_caught() {
+        let yaml = VALID.replace(r#"id: "details""#, r#"id: "name""#);
+        assert_eq!(
+            problems(&yaml),
+            vec!["workflows.test.sections.Start[0].id: duplicate input id 'name' (already defined at workflows.test.sections.Details[0])"]
+        );
+        // Inputs in a group only have to be unique within that group, but the group itself can't clash with an input
+        let yaml = VALID.replace(
+            r#"- { id: "details", label: "Details", type: "multiline", when: "${name}" }"#,
+            r#"- { group: "name", label: "Steps", inputs: [ { id: "name", label: "Name", type: "text" }, { id: "name", label: "Again", type: "text" } ] }"#,
+        );
+        assert_eq!(
+            problems(&yaml),
+            vec![
+                "workflows.test.sections.Details[0].inputs[1].id: duplicate input id 'name' in group 'name' (already defined at workflows.test.sections.Details[0].inputs[0])",
+                "workflows.test.sections.Start[0].id: duplicate input id 'name' (already defined at workflows.test.sections.Details[0])",
+            ]
+        );
+    }
+    #[test]
+    fn unreachable_sections_are_caught() {
+        let yaml = VALID.replace(
+            "    Details:\n",
+            "    Orphan:\n      - { text: \"Back\", link: \"Start\", tags: [] }\n    Details:\n",
+        );
+        assert_eq!(
+            problems(&yaml),
+            vec!["workflows.test.sections.Orphan: section can't be reached from the index (or from any section in `prefill.sections`)"]
+        );
+        // Links can start a workflow at a section instead
+        let yaml = format!("{}  prefill:\n    sections: [ \"Orphan\" ]\n", yaml);
+        assert_eq!(problems(&yaml), Vec::<String>::new());
+    }
+    #[test]
+    fn conditions_must_reference_inputs() {
+        let yaml = VALID.replace(r#"when: "${name}""#, r#"when: "${nmae}""#);
+        assert_eq!(
+            problems(&yaml),
+            vec!["workflows.test.sections.Details[0].when: condition references '${nmae}', but no input has that id"]
+        );
+        // Inputs in a group can reference the other inputs in the same entry
+        let yaml = VALID.replace(
+            r#"- { id: "details", label: "Details", type: "multiline", when: "${name}" }"#,
+            r#"- { group: "steps", label: "Steps", inputs: [ { id: "action", label: "Action", type: "text" }, { id: "result", label: "Result", type: "text", when: "${action} and ${name}" } ] }"#,
+        );
+        assert_eq!(problems(&yaml), Vec::<String>::new());
+    }
+    /// Turns the endpoint of the valid workflow into a report with the given text.
+    fn with_report(text: &str) -> String {
+        VALID.replace(
+            r#"    Done: "Thanks!""#,
+            &format!(
+                "    Done:\n      preamble: \"Thanks!\"\n      text: \"{}\"\n      dest_text: \"Send\"",
+                text
+            ),
+        )
+    }
+
+    #[test]
+    fn report_templates_must_reference_inputs() {
+        assert_eq!(
+            problems(&with_report("${name} (${_locale}): ${details}")),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            problems(&with_report("${name}: ${detials}")),
+            vec!["workflows.test.endpoints.Done.text: reference to '${detials}', but no input has that id"]
+        );
+    }
+    #[test]
+    fn report_templates_must_reference_reachable_inputs() {
+        // This input is only on the way to another endpoint
+        let yaml = with_report("${name}: ${other}")
+            .replace(
+                r#"      - { text: "Next", link: "Details", tags: [] }"#,
+                "      - { text: \"Next\", link: \"Details\", tags: [] }\n      - { text: \"Other\", link: \"Other\", tags: [] }",
+            )
+            .replace(
+                "  endpoints:\n",
+                "    Other:\n      - { id: \"other\", label: \"Other\", type: \"text\" }\n      - { text: \"Done\", link: \"endpoint:Elsewhere\", tags: [] }\n  endpoints:\n    Elsewhere: \"Bye!\"\n",
+            );
+        assert_eq!(
+            problems(&yaml),
+            vec!["workflows.test.endpoints.Done.text: reference to '${other}', but that input can't be reached before this endpoint"]
+        );
+        let parsed = workflows(&yaml);
+        assert!(matches!(
+            check_template_refs("test.yml", "test", &parsed["test"]),
+            Err(ParserError::UnreachableTemplateRef { ref field, ref id, .. }) if field == "text" && id == "other"
+        ));
+        assert!(check_template_refs(
+            "test.yml",
+            "test",
+            &workflows(&with_report("${name}"))["test"]
+        )
+        .is_ok());
+    }
+
+    /// Compares the given translation of workflows against the given reference, returning each problem as `<path>: <message>`.
+    fn drift(reference: &str, translated: &str) -> Vec<String> {
+        let reference = LangCfg {
+            locale: "en-US",
+            filename: "en-US.yml",
+            workflows: workflows(reference),
+        };
+        let translated = LangCfg {
+            locale: "fr-FR",
+            filename: "fr-FR.yml",
+            workflows: workflows(translated),
+        };
+        let mut problems = Vec::new();
+        check_consistency(&reference, &translated, &mut problems);
+        problems
+            .iter()
+            .map(|problem| format!("{}: {}", problem.path, problem.msg))
+            .collect()
+    }
+
+    #[test]
+    fn translated_text_isnt_drift() {
+        let translated = VALID
+            .replace(r#"label: "Name""#, r#"label: "Nom""#)
+            .replace(r#"text: "Next""#, r#"text: "Suivant""#)
+            .replace("Thanks!", "Merci !");
+        assert_eq!(drift(VALID, &translated), Vec::<String>::new());
+    }
+    #[test]
+    fn structural_drift_is_caught() {
+        // A missing section (its contents have moved, so the inputs are the same)
+        let translated = VALID
+            .replace(r#"link: "Details""#, r#"link: "endpoint:Done""#)
+            .replace("    Details:\n", "    Unused:\n");
+        assert_eq!(
+            drift(VALID, &translated),
+            vec![
+                "workflows.test.sections: missing section 'Details', which is in the reference locale 'en-US'",
+                "workflows.test.sections: extra section 'Unused', which isn't in the reference locale 'en-US'",
+                "workflows.test.sections.Start[1].link: progression links to 'endpoint:Done', but the matching progression in the reference locale 'en-US' links to 'Details'",
+            ]
+        );
+        // A different input and different tags
+        let translated = VALID
+            .replace(r#"id: "details""#, r#"id: "detail""#)
+            .replace(
+                r#"link: "Details", tags: []"#,
+                r#"link: "Details", tags: [ "C:bug" ]"#,
+            );
+        assert_eq!(
+            drift(VALID, &translated),
+            vec![
+                "workflows.test.sections.Start[1].tags: progression has tags [\"C:bug\"], but the matching progression in the reference locale 'en-US' has tags []",
+                "workflows.test: missing input 'details', which is in the reference locale 'en-US'",
+                "workflows.test: extra input 'detail', which isn't in the reference locale 'en-US'",
+            ]
+        );
+    }
+    #[test]
+    fn order_drift_is_caught() {
+        let reference = VALID.replace("  index:", "  order: 1\n  index:");
+        let translated = VALID.replace("  index:", "  order: 2\n  index:");
+        assert_eq!(
+            drift(&reference, &translated),
+            vec!["workflows.test.order: order is Some(2), but it's Some(1) in the reference locale 'en-US'"]
+        );
+        assert_eq!(
+            drift(&reference, VALID),
+            vec!["workflows.test.order: order is None, but it's Some(1) in the reference locale 'en-US'"]
+        );
+    }
+    #[test]
+    fn missing_workflows_are_caught() {
+        let translated = VALID.replace("test:\n", "other:\n");
+        assert_eq!(
+            drift(VALID, &translated),
+            vec![
+                "workflows: missing workflow 'test', which is in the reference locale 'en-US'",
+                "workflows: extra workflow 'other', which isn't in the reference locale 'en-US'",
+            ]
+        );
+    }
+}
@@ -0,0 +1,620 @@
+use crate::errors::ConditionError;
+use crate::form::FormValue;
+#[cfg(feature = "schema")]
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// A condition that determines whether or not something should happen, written in a small expression language over the tags the user has
+/// accumulated and the values they've entered into inputs. For example, `tags contains "A:frontend" and ${severity} == "high"`.
+///
+/// The language supports:
+///
+/// - `tags contains "<tag>"` -- whether or not the user has accumulated the given tag in a previous section
+/// - `${id} == "<value>"`/`${id} != "<value>"` -- comparing the value of an input
+/// - `${id} contains "<value>"` -- whether or not an input that allows multiple options has the given option selected (for any other
+///   input, this is the same as `==`)
+/// - `${id} < 5` (and `<=`, `>`, `>=`) -- numerical comparisons (these are always false if the value isn't a number), which also work
+///   for dates and times (e.g. `${date} >= "2022-01-01"`)
+/// - `${id}` -- whether or not an input has a value (booleans that are `false` count as not having a value)
+/// - `not`, `and`, `or`, and parentheses for grouping
+///
+/// Literal values are written in double quotes, except for numbers, which can also be written bare (`1.2.3` isn't a number, so it has
+/// to be quoted).
+///
+/// Conditions are parsed when the configuration is, so syntax errors will be caught before anything is built.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "String", into = "String")]
+pub struct Condition {
+    /// The original text of the condition (this is what we serialize).
+    src: String,
+    /// The parsed expression.
+    expr: Expr,
+}
+impl Condition {
+    /// Parses a new condition from its source text.
+    pub fn new(src: &str) -> Result<Self, ConditionError> {
+        let tokens = tokenize(src)?;
+        let mut parser = ExprParser {
+            src,
+            tokens: &tokens,
+            pos: 0,
+        };
+        let expr = parser.parse_or()?;
+        // Anything left over is a syntax error
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(ConditionError::UnexpectedToken {
+                cond: src.to_string(),
+                token: token.to_string(),
+            });
+        }
+
+        Ok(Self {
+            src: src.to_string(),
+            expr,
+        })
+    }
+    /// Evaluates the condition with the given tags and a function that gets the current value of the input with the given ID (if it exists).
+    pub fn eval(&self, tags: &[String], value: impl Fn(&str) -> Option<FormValue>) -> bool {
+        self.expr.eval(tags, &value)
+    }
+    /// Gets the IDs of all the inputs this condition references, so they can be checked.
+    pub fn vars(&self) -> Vec<&str> {
+        let mut vars = Vec::new();
+        self.expr.vars(&mut vars);
+        vars
+    }
+}
+impl TryFrom<String> for Condition {
+    type Error = ConditionError;
+
+    fn try_from(src: String) -> Result<Self, Self::Error> {
+        Self::new(&src)
+    }
+}
+impl From<Condition> for String {
+    fn from(cond: Condition) -> Self {
+        cond.src
+    }
+}
+#[cfg(feature = "schema")]
+impl JsonSchema for Condition {
+    fn schema_name() -> String {
+        "Condition".to_string()
+    }
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+/// A parsed condition expression.
+#[derive(Debug, Clone)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Cmp(Operand, CmpOp, Operand),
+    /// A single operand on its own, which is true if it has a value.
+    Truthy(Operand),
+}
+impl Expr {
+    fn eval(&self, tags: &[String], value: &dyn Fn(&str) -> Option<FormValue>) -> bool {
+        match self {
+            Self::Or(l, r) => l.eval(tags, value) || r.eval(tags, value),
+            Self::And(l, r) => l.eval(tags, value) && r.eval(tags, value),
+            Self::Not(expr) => !expr.eval(tags, value),
+            Self::Cmp(Operand::Tags, CmpOp::Contains, r) => {
+                let tag = r.resolve(value).to_string();
+                tags.iter().any(|t| t == &tag)
+            }
+            Self::Cmp(l, op, r) => {
+                let l = l.resolve(value);
+                let r = r.resolve(value);
+                match op {
+                    CmpOp::Eq => l.to_string() == r.to_string(),
+                    CmpOp::Ne => l.to_string() != r.to_string(),
+                    CmpOp::Contains => {
+                        let r = r.to_string();
+                        l.items().iter().any(|v| v == &r)
+                    }
+                    CmpOp::Lt | CmpOp::Le | CmpOp::Gt | CmpOp::Ge => {
+                        let is_date =
+                            matches!(l, FormValue::Date(_)) || matches!(r, FormValue::Date(_));
+                        let ordering = if is_date {
+                            // Dates and times are always zero-padded, so they can be compared as text (as long as both are there)
+                            if l.is_empty() || r.is_empty() {
+                                None
+                            } else {
+                                Some(l.to_string().cmp(&r.to_string()))
+                            }
+                        } else {
+                            match (l.as_number(), r.as_number()) {
+                                (Some(l), Some(r)) => l.partial_cmp(&r),
+                                _ => None,
+                            }
+                        };
+                        match ordering {
+                            Some(ordering) => match op {
+                                CmpOp::Lt => ordering.is_lt(),
+                                CmpOp::Le => ordering.is_le(),
+                                CmpOp::Gt => ordering.is_gt(),
+                                _ => ordering.is_ge(),
+                            },
+                            None => false,
+                        }
+                    }
+                }
+            }
+            Self::Truthy(operand) => operand.resolve(value).is_truthy(),
+        }
+    }
+    fn vars<'a>(&'a self, vars: &mut Vec<&'a str>) {
+        match self {
+            Self::Or(l, r) | Self::And(l, r) => {
+                l.vars(vars);
+                r.vars(vars);
+            }
+            Self::Not(expr) => expr.vars(vars),
+            Self::Cmp(l, _, r) => {
+                l.vars(vars);
+                r.vars(vars);
+            }
+            Self::Truthy(operand) => operand.vars(vars),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    /// The tags the user has accumulated (only usable with `contains`).
+    Tags,
+    /// The value of the input with the given ID.
+    Var(String),
+    /// A literal value (numbers are stored as they were written).
+    Lit(String),
+}
+impl Operand {
+    fn resolve(&self, value: &dyn Fn(&str) -> Option<FormValue>) -> FormValue {
+        match self {
+            // This is never resolved directly
+            Self::Tags => FormValue::Single(String::new()),
+            // Inputs that haven't been filled out are treated as empty
+            Self::Var(id) => value(id).unwrap_or_else(|| FormValue::Single(String::new())),
+            Self::Lit(lit) => FormValue::Single(lit.to_string()),
+        }
+    }
+    fn vars<'a>(&'a self, vars: &mut Vec<&'a str>) {
+        if let Self::Var(id) = self {
+            vars.push(id);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Contains,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    Str(String),
+    Num(String),
+    Var(String),
+    Word(String),
+    Op(String),
+}
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LParen => write!(f, "("),
+            Self::RParen => write!(f, ")"),
+            Self::Str(s) => write!(f, "{:?}", s),
+            Self::Num(s) | Self::Word(s) | Self::Op(s) => write!(f, "{}", s),
+            Self::Var(id) => write!(f, "${{{}}}", id),
+        }
+    }
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, ConditionError> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => (),
+            '(' => tokens.push(Token::LParen),
+            ')' => tokens.push(Token::RParen),
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => s.push(escaped),
+                            None => {
+                                return Err(ConditionError::UnterminatedString {
+                                    cond: src.to_string(),
+                                })
+                            }
+                        },
+                        Some(c) => s.push(c),
+                        None => {
+                            return Err(ConditionError::UnterminatedString {
+                                cond: src.to_string(),
+                            })
+                        }
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                let mut id = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => id.push(c),
+                        None => {
+                            return Err(ConditionError::UnexpectedEnd {
+                                cond: src.to_string(),
+                            })
+                        }
+                    }
+                }
+                tokens.push(Token::Var(id));
+            }
+            '=' | '!' | '<' | '>' => {
+                let mut op = c.to_string();
+                if chars.peek() == Some(&'=') {
+                    op.push(chars.next().unwrap());
+                }
+                tokens.push(Token::Op(op));
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let mut num = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || c == '.' {
+                        num.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                // Things like `1.2.3` or a lone `-` would otherwise be compared as text, which is never what anyone wants
+                if num.parse::<f64>().is_err() {
+                    return Err(ConditionError::InvalidNumber {
+                        cond: src.to_string(),
+                        num,
+                    });
+                }
+                tokens.push(Token::Num(num));
+            }
+            c if c.is_alphabetic() => {
+                let mut word = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        word.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+            c => {
+                return Err(ConditionError::UnexpectedToken {
+                    cond: src.to_string(),
+                    token: c.to_string(),
+                })
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// A recursive-descent parser for conditions.
+struct ExprParser<'a> {
+    src: &'a str,
+    tokens: &'a [Token],
+    pos: usize,
+}
+impl<'a> ExprParser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+    fn next(&mut self) -> Result<&'a Token, ConditionError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or_else(|| ConditionError::UnexpectedEnd {
+                cond: self.src.to_string(),
+            })?;
+        self.pos += 1;
+        Ok(token)
+    }
+    fn unexpected(&self, token: &Token) -> ConditionError {
+        ConditionError::UnexpectedToken {
+            cond: self.src.to_string(),
+            token: token.to_string(),
+        }
+    }
+    fn is_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w == word)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ConditionError> {
+        let mut expr = self.parse_and()?;
+        while self.is_word("or") {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+    fn parse_and(&mut self) -> Result<Expr, ConditionError> {
+        let mut expr = self.parse_not()?;
+        while self.is_word("and") {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+    fn parse_not(&mut self) -> Result<Expr, ConditionError> {
+        if self.is_word("not") {
+            self.pos += 1;
+            Ok(Expr::Not(Box::new(self.parse_not()?)))
+        } else {
+            self.parse_atom()
+        }
+    }
+    fn parse_atom(&mut self) -> Result<Expr, ConditionError> {
+        if let Some(Token::LParen) = self.peek() {
+            self.pos += 1;
+            let expr = self.parse_or()?;
+            return match self.next()? {
+                Token::RParen => Ok(expr),
+                token => Err(self.unexpected(token)),
+            };
+        }
+        let l = self.parse_operand()?;
+        let op = match self.peek() {
+            Some(Token::Op(op)) => match op.as_str() {
+                "==" => CmpOp::Eq,
+                "!=" => CmpOp::Ne,
+                "<" => CmpOp::Lt,
+                "<=" => CmpOp::Le,
+                ">" => CmpOp::Gt,
+                ">=" => CmpOp::Ge,
+                _ => return Err(self.unexpected(self.peek().unwrap())),
+            },
+            Some(Token::Word(w)) if w == "contains" => CmpOp::Contains,
+            // There's no comparison, so this must just be checking if the operand has a value
+            _ => {
+                return match l {
+                    Operand::Tags => Err(ConditionError::InvalidTagsUsage {
+                        cond: self.src.to_string(),
+                    }),
+                    l => Ok(Expr::Truthy(l)),
+                }
+            }
+        };
+        self.pos += 1;
+        let r = self.parse_operand()?;
+        // `tags` can only ever be on the left of a `contains`
+        let uses_tags = matches!(l, Operand::Tags) || matches!(r, Operand::Tags);
+        let valid_tags = matches!(
+            (&l, op, &r),
+            (
+                Operand::Tags,
+                CmpOp::Contains,
+                Operand::Var(_) | Operand::Lit(_)
+            )
+        );
+        if uses_tags && !valid_tags {
+            Err(ConditionError::InvalidTagsUsage {
+                cond: self.src.to_string(),
+            })
+        } else {
+            Ok(Expr::Cmp(l, op, r))
+        }
+    }
+    fn parse_operand(&mut self) -> Result<Operand, ConditionError> {
+        match self.next()? {
+            Token::Word(w) if w == "tags" => Ok(Operand::Tags),
+            Token::Var(id) => Ok(Operand::Var(id.to_string())),
+            Token::Str(s) | Token::Num(s) => Ok(Operand::Lit(s.to_string())),
+            token => Err(self.unexpected(token)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn eval(cond: &str, tags: &[&str], values: &[(&str, FormValue)]) -> bool {
+        let tags: Vec<String> = tags.iter().map(|tag| tag.to_string()).collect();
+        let values: HashMap<&str, FormValue> = values.iter().cloned().collect();
+        Condition::new(cond)
+            .unwrap()
+            .eval(&tags, |id| values.get(id).cloned())
+    }
+    fn single(text: &str) -> FormValue {
+        FormValue::Single(text.to_string())
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        // This is `a or (b and c)`, not `(a or b) and c`
+        assert!(eval(
+            r#"tags contains "a" or tags contains "b" and tags contains "c""#,
+            &["a"],
+            &[]
+        ));
+        assert!(!eval(
+            r#"(tags contains "a" or tags contains "b") and tags contains "c""#,
+            &["a"],
+            &[]
+        ));
+    }
+    #[test]
+    fn not_binds_tighter_than_and() {
+        assert!(eval(
+            r#"not tags contains "a" and tags contains "b""#,
+            &["b"],
+            &[]
+        ));
+        assert!(!eval(
+            r#"not (tags contains "a" and tags contains "b")"#,
+            &["a", "b"],
+            &[]
+        ));
+    }
+    #[test]
+    fn tags_contains_checks_accumulated_tags() {
+        assert!(eval(r#"tags contains "C:bug""#, &["C:bug"], &[]));
+        assert!(!eval(r#"tags contains "C:bug""#, &["C:docs"], &[]));
+        // The tag can come from an input too
+        assert!(eval(
+            "tags contains ${tag}",
+            &["C:bug"],
+            &[("tag", single("C:bug"))]
+        ));
+    }
+    #[test]
+    fn tags_must_be_used_with_contains() {
+        for cond in [
+            "tags",
+            r#"tags == "C:bug""#,
+            r#""C:bug" contains tags"#,
+            "tags contains tags",
+        ] {
+            assert!(
+                matches!(
+                    Condition::new(cond),
+                    Err(ConditionError::InvalidTagsUsage { .. })
+                ),
+                "'{}' should be rejected",
+                cond
+            );
+        }
+    }
+    #[test]
+    fn contains_checks_selected_options() {
+        let areas = FormValue::List(vec!["Frontend".to_string(), "Backend".to_string()]);
+        assert!(eval(
+            r#"${areas} contains "Backend""#,
+            &[],
+            &[("areas", areas.clone())]
+        ));
+        assert!(!eval(
+            r#"${areas} contains "Docs""#,
+            &[],
+            &[("areas", areas)]
+        ));
+        // Options can contain commas, so this mustn't match part of one
+        let areas = FormValue::List(vec!["Frontend, mostly".to_string()]);
+        assert!(!eval(
+            r#"${areas} contains "Frontend""#,
+            &[],
+            &[("areas", areas)]
+        ));
+    }
+    #[test]
+    fn ordering_is_numeric_but_equality_is_textual() {
+        // As text, "9" would come after "10"
+        assert!(eval("${n} < 10", &[], &[("n", single("9"))]));
+        assert!(eval(
+            "${n} >= -1.5",
+            &[],
+            &[("n", FormValue::Number(Some(0.0)))]
+        ));
+        assert!(!eval("${n} == 5", &[], &[("n", single("5.0"))]));
+        assert!(eval(r#"${n} == "5.0""#, &[], &[("n", single("5.0"))]));
+    }
+    #[test]
+    fn ordering_non_numbers_is_false() {
+        assert!(!eval("${n} < 10", &[], &[("n", single("abc"))]));
+        assert!(!eval("${n} >= 10", &[], &[("n", single("abc"))]));
+        assert!(!eval("${n} < 10", &[], &[]));
+    }
+    #[test]
+    fn dates_are_ordered_as_text() {
+        let date = FormValue::Date("2022-03-01".to_string());
+        assert!(eval(r#"${d} >= "2022-01-01""#, &[], &[("d", date.clone())]));
+        assert!(!eval(r#"${d} < "2022-01-01""#, &[], &[("d", date)]));
+        // An empty date isn't before everything
+        let empty = FormValue::Date(String::new());
+        assert!(!eval(r#"${d} < "2022-01-01""#, &[], &[("d", empty)]));
+    }
+    #[test]
+    fn bare_inputs_check_for_a_value() {
+        assert!(eval("${a}", &[], &[("a", single("x"))]));
+        assert!(!eval("${a}", &[], &[("a", single(""))]));
+        assert!(!eval("${a}", &[], &[("a", FormValue::Boolean(false))]));
+        assert!(eval("${a}", &[], &[("a", FormValue::Boolean(true))]));
+        assert!(!eval("${a}", &[], &[]));
+    }
+    #[test]
+    fn bare_words_are_rejected() {
+        for cond in ["areas contains \"Backend\"", "${a} == high", "${a} and b"] {
+            assert!(
+                matches!(
+                    Condition::new(cond),
+                    Err(ConditionError::UnexpectedToken { .. })
+                ),
+                "'{}' should be rejected",
+                cond
+            );
+        }
+    }
+    #[test]
+    fn malformed_numbers_are_rejected() {
+        for cond in ["${v} == 1.2.3", "${v} < -", "${v} > ."] {
+            assert!(
+                matches!(
+                    Condition::new(cond),
+                    Err(ConditionError::InvalidNumber { .. })
+                ),
+                "'{}' should be rejected",
+                cond
+            );
+        }
+    }
+    #[test]
+    fn syntax_errors_are_caught() {
+        assert!(matches!(
+            Condition::new(r#"${a} == "b"#),
+            Err(ConditionError::UnterminatedString { .. })
+        ));
+        assert!(matches!(
+            Condition::new("${a"),
+            Err(ConditionError::UnexpectedEnd { .. })
+        ));
+        assert!(matches!(
+            Condition::new("(${a}"),
+            Err(ConditionError::UnexpectedEnd { .. })
+        ));
+        assert!(matches!(
+            Condition::new("${a} ${b}"),
+            Err(ConditionError::UnexpectedToken { .. })
+        ));
+        assert!(matches!(
+            Condition::new("${a} =! 5"),
+            Err(ConditionError::UnexpectedToken { .. })
+        ));
+    }
+    #[test]
+    fn vars_are_collected() {
+        let cond = Condition::new(r#"${a} == "x" or not (${b} and tags contains ${c})"#).unwrap();
+        assert_eq!(cond.vars(), vec!["a", "b", "c"]);
+    }
+}
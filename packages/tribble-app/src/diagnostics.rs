@@ -0,0 +1,785 @@
+use crate::condition::Condition;
+use crate::template::Template;
+use serde::de::DeserializeOwned;
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
+use std::fmt;
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
+
+/// A rich description of why a configuration file couldn't be parsed, pointing to the exact element that caused the problem. Because most
+/// of the configuration types are untagged enums, the raw errors from Serde are almost useless ("data did not match any variant..."), so we
+/// walk the document ourselves to figure out what the user was probably trying to write.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// The file the problem is in.
+    pub filename: String,
+    /// A description of what's wrong.
+    pub msg: String,
+    /// The path to the offending element in the YAML document (e.g. `workflows.test.sections."Report Bug"[3]`), if we could work it out.
+    pub path: Option<String>,
+    /// The line and column of the offending element (both starting from 1), if we could work them out.
+    pub location: Option<(usize, usize)>,
+    /// A rendered excerpt of the file around the offending element.
+    pub snippet: Option<String>,
+}
+impl Diagnostic {
+    /// Creates a diagnostic for the given raw error from parsing the given file contents.
+    pub fn new(filename: &str, contents: &str, err: serde_yaml::Error) -> Self {
+        // If the file isn't even valid YAML, the raw error already has everything we need
+        let value: Value = match serde_yaml::from_str(contents) {
+            Ok(value) => value,
+            Err(_) => return Self::from_raw(filename, contents, err),
+        };
+
+        match diagnose_config(&value) {
+            Err(Mismatch { path, msg }) => {
+                let path = fmt_path(&path);
+                let location = locate(contents).get(&path).copied();
+                Self {
+                    filename: filename.to_string(),
+                    msg,
+                    snippet: location.map(|loc| render_snippet(contents, loc)),
+                    location,
+                    path: Some(path),
+                }
+            }
+            // Our checks are looser than the real types, so they might not have found anything, in which case we'll fall back to the raw error
+            Ok(()) => Self::from_raw(filename, contents, err),
+        }
+    }
+    /// Creates a diagnostic directly from a raw error, using whatever location information it has.
+    fn from_raw(filename: &str, contents: &str, err: serde_yaml::Error) -> Self {
+        let location = err.location().map(|loc| (loc.line(), loc.column()));
+        Self {
+            filename: filename.to_string(),
+            msg: err.to_string(),
+            path: None,
+            snippet: location.map(|loc| render_snippet(contents, loc)),
+            location,
+        }
+    }
+}
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)?;
+        match self.location {
+            Some((line, col)) => write!(f, "\n --> {}:{}:{}", self.filename, line, col)?,
+            None => write!(f, "\n --> {}", self.filename)?,
+        };
+        if let Some(path) = &self.path {
+            write!(f, " (at `{}`)", path)?;
+        }
+        if let Some(snippet) = &self.snippet {
+            write!(f, "\n{}", snippet)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single segment of a path through a YAML document.
+#[derive(Debug, Clone)]
+enum Seg {
+    Key(String),
+    Index(usize),
+}
+
+/// An element of the document that doesn't match what it should be, which is what we're trying to find.
+struct Mismatch {
+    path: Vec<Seg>,
+    msg: String,
+}
+impl Mismatch {
+    fn new(path: &[Seg], msg: impl Into<String>) -> Self {
+        Self {
+            path: path.to_vec(),
+            msg: msg.into(),
+        }
+    }
+}
+
+/// Formats a map key for use in a path, quoting it if it isn't a simple identifier.
+pub(crate) fn fmt_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        key.to_string()
+    } else {
+        format!("{:?}", key)
+    }
+}
+
+/// Formats a path through a YAML document in the same way the user would describe it (e.g. `workflows.test.sections."Report Bug"[3]`).
+fn fmt_path(path: &[Seg]) -> String {
+    let mut formatted = String::new();
+    for seg in path {
+        match seg {
+            Seg::Key(key) => {
+                if !formatted.is_empty() {
+                    formatted.push('.');
+                }
+                formatted.push_str(&fmt_key(key));
+            }
+            Seg::Index(idx) => formatted.push_str(&format!("[{}]", idx)),
+        }
+    }
+
+    formatted
+}
+
+/// Appends a segment to a path, producing a new one.
+fn join(path: &[Seg], seg: Seg) -> Vec<Seg> {
+    let mut path = path.to_vec();
+    path.push(seg);
+    path
+}
+
+/// Gets a field from a mapping by its string key.
+fn get<'a>(map: &'a Mapping, key: &str) -> Option<&'a Value> {
+    map.get(&Value::String(key.to_string()))
+}
+
+/// Iterates over the entries of a mapping, producing a string for each key. YAML allows non-string keys, but Tribble never uses them.
+fn entries(map: &Mapping) -> impl Iterator<Item = (String, &Value)> + '_ {
+    map.iter().map(|(k, v)| {
+        let key = match k {
+            Value::String(key) => key.to_string(),
+            _ => serde_yaml::to_string(k)
+                .unwrap_or_default()
+                .trim_start_matches("---")
+                .trim()
+                .to_string(),
+        };
+        (key, v)
+    })
+}
+
+/// Deserializes the given value into a real configuration type, converting any error into a mismatch at the given path. This is used for
+/// the types that aren't untagged, which already have decent error messages.
+fn check_as<T: DeserializeOwned>(value: &Value, path: &[Seg]) -> Result<(), Mismatch> {
+    serde_yaml::from_value::<T>(value.clone())
+        .map(|_| ())
+        .map_err(|err| Mismatch::new(path, err.to_string()))
+}
+
+/// The kinds of simple fields we check for in the untagged types.
+enum Kind {
+    Str,
+    StrList,
+    Bool,
+    /// A string that must be a valid condition.
+    Condition,
+    /// A string that must be a valid template.
+    Template,
+}
+
+/// Checks that a field in a mapping is of the given kind. If `what` is provided, it describes the variant we think the user was trying
+/// to write, which will be mentioned in the error message.
+fn check_field(
+    map: &Mapping,
+    key: &str,
+    kind: Kind,
+    required: bool,
+    path: &[Seg],
+    what: &str,
+) -> Result<(), Mismatch> {
+    let prefix = if what.is_empty() {
+        String::new()
+    } else {
+        format!("this looks like {}, but ", what)
+    };
+    let value = match get(map, key) {
+        Some(value) => value,
+        None if required => {
+            return Err(Mismatch::new(
+                path,
+                format!("{}the required field `{}` is missing", prefix, key),
+            ))
+        }
+        None => return Ok(()),
+    };
+    let path = join(path, Seg::Key(key.to_string()));
+    match (kind, value) {
+        (Kind::Str, Value::String(_)) | (Kind::Bool, Value::Bool(_)) => Ok(()),
+        (Kind::StrList, Value::Sequence(seq)) => {
+            for (i, elem) in seq.iter().enumerate() {
+                if !matches!(elem, Value::String(_)) {
+                    return Err(Mismatch::new(
+                        &join(&path, Seg::Index(i)),
+                        format!("{}every element of `{}` must be a string", prefix, key),
+                    ));
+                }
+            }
+            Ok(())
+        }
+        (Kind::Condition, Value::String(cond)) => match Condition::new(cond) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Mismatch::new(&path, format!("{}{}", prefix, err))),
+        },
+        (Kind::Template, Value::String(template)) => match Template::new(template) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Mismatch::new(&path, format!("{}{}", prefix, err))),
+        },
+        (Kind::Str, _) | (Kind::Condition, _) | (Kind::Template, _) => Err(Mismatch::new(
+            &path,
+            format!("{}the field `{}` must be a string", prefix, key),
+        )),
+        (Kind::StrList, _) => Err(Mismatch::new(
+            &path,
+            format!("{}the field `{}` must be a list of strings", prefix, key),
+        )),
+        (Kind::Bool, _) => Err(Mismatch::new(
+            &path,
+            format!("{}the field `{}` must be `true` or `false`", prefix, key),
+        )),
+    }
+}
+
+/// Gets a field that must be a mapping.
+fn expect_map<'a>(map: &'a Mapping, key: &str, path: &[Seg]) -> Result<&'a Mapping, Mismatch> {
+    match get(map, key) {
+        Some(Value::Mapping(inner)) => Ok(inner),
+        Some(_) => Err(Mismatch::new(
+            &join(path, Seg::Key(key.to_string())),
+            format!("the field `{}` must be a map", key),
+        )),
+        None => Err(Mismatch::new(
+            path,
+            format!("the required field `{}` is missing", key),
+        )),
+    }
+}
+
+fn diagnose_config(value: &Value) -> Result<(), Mismatch> {
+    let map = match value {
+        Value::Mapping(map) => map,
+        _ => return Err(Mismatch::new(&[], "the configuration file must be a map")),
+    };
+    if let Some(languages) = get(map, "languages") {
+        let path = [Seg::Key("languages".to_string())];
+        let languages = match languages {
+            Value::Mapping(languages) => languages,
+            _ => {
+                return Err(Mismatch::new(
+                    &path,
+                    "this looks like a root config file, but `languages` must be a map of locales to language config files",
+                ))
+            }
+        };
+        for (locale, file) in entries(languages) {
+            if !matches!(file, Value::String(_)) {
+                return Err(Mismatch::new(
+                    &join(&path, Seg::Key(locale)),
+                    "this looks like a root config file, but each language must map to the filename of a language config file",
+                ));
+            }
+        }
+        check_field(
+            map,
+            "reference_locale",
+            Kind::Str,
+            false,
+            &[],
+            "a root config file",
+        )?;
+        return Ok(());
+    }
+    if get(map, "workflows").is_none() {
+        return Err(Mismatch::new(&[], "expected either a `languages` map (for a root config file) or a `workflows` map (for a language config file)"));
+    }
+    check_field(map, "input_err_msg", Kind::Str, false, &[], "")?;
+    if let Some(resume) = get(map, "resume") {
+        check_as::<crate::parser::ResumeText>(resume, &[Seg::Key("resume".to_string())])?;
+    }
+    if let Some(errors) = get(map, "errors") {
+        check_as::<crate::parser::ErrorText>(errors, &[Seg::Key("errors".to_string())])?;
+    }
+    if let Some(landing) = get(map, "landing") {
+        check_as::<crate::parser::LandingText>(landing, &[Seg::Key("landing".to_string())])?;
+    }
+    let workflows = expect_map(map, "workflows", &[])?;
+    for (name, workflow) in entries(workflows) {
+        let path = vec![Seg::Key("workflows".to_string()), Seg::Key(name)];
+        diagnose_workflow(workflow, &path)?;
+    }
+
+    Ok(())
+}
+
+fn diagnose_workflow(value: &Value, path: &[Seg]) -> Result<(), Mismatch> {
+    let map = match value {
+        Value::Mapping(map) => map,
+        _ => return Err(Mismatch::new(path, "a workflow must be a map")),
+    };
+    check_field(map, "title", Kind::Str, true, path, "")?;
+    check_field(map, "index", Kind::Str, true, path, "")?;
+    check_field(map, "description", Kind::Str, false, path, "")?;
+    check_field(map, "icon", Kind::Str, false, path, "")?;
+    if let Some(order) = get(map, "order") {
+        check_as::<i32>(order, &join(path, Seg::Key("order".to_string())))?;
+    }
+    if let Some(prefill) = get(map, "prefill") {
+        check_as::<crate::parser::Prefill>(prefill, &join(path, Seg::Key("prefill".to_string())))?;
+    }
+    if let Some(share) = get(map, "share") {
+        check_as::<crate::parser::ShareLink>(share, &join(path, Seg::Key("share".to_string())))?;
+    }
+    let sections = expect_map(map, "sections", path)?;
+    for (name, section) in entries(sections) {
+        let path = join(path, Seg::Key("sections".to_string()));
+        let path = join(&path, Seg::Key(name));
+        let elems = match section {
+            Value::Sequence(elems) => elems,
+            _ => return Err(Mismatch::new(&path, "a section must be a list of elements")),
+        };
+        for (i, elem) in elems.iter().enumerate() {
+            diagnose_section_elem(elem, &join(&path, Seg::Index(i)))?;
+        }
+    }
+    let endpoints = expect_map(map, "endpoints", path)?;
+    for (name, endpoint) in entries(endpoints) {
+        let path = join(path, Seg::Key("endpoints".to_string()));
+        diagnose_endpoint(endpoint, &join(&path, Seg::Key(name)))?;
+    }
+    if get(map, "fields").is_some() {
+        let fields = expect_map(map, "fields", path)?;
+        let path = join(path, Seg::Key("fields".to_string()));
+        for (name, _) in entries(fields) {
+            check_field(fields, &name, Kind::Template, true, &path, "")?;
+        }
+    }
+
+    Ok(())
+}
+
+fn diagnose_section_elem(value: &Value, path: &[Seg]) -> Result<(), Mismatch> {
+    let map = match value {
+        Value::String(_) => return Ok(()),
+        Value::Mapping(map) => map,
+        _ => return Err(Mismatch::new(path, "expected text (a string), a progression (a map with `text`, `link`, and `tags`), or an input (a map with `id` and `label`)")),
+    };
+    let has = |key: &str| get(map, key).is_some();
+    // We guess what the user meant by the fields they've provided
+    if has("redirect") {
+        let what = "a redirect";
+        check_field(map, "redirect", Kind::Str, true, path, what)?;
+        check_field(map, "when", Kind::Condition, false, path, what)?;
+        check_field(map, "tags", Kind::StrList, false, path, what)?;
+        Ok(())
+    } else if !has("link") && has("text") && has("when") && !has("id") && !has("label") {
+        let what = "conditional text";
+        check_field(map, "text", Kind::Str, true, path, what)?;
+        check_field(map, "when", Kind::Condition, true, path, what)?;
+        Ok(())
+    } else if has("link") || (has("text") && !has("id") && !has("label")) {
+        let what = "a progression";
+        check_field(map, "text", Kind::Str, true, path, what)?;
+        check_field(map, "link", Kind::Str, true, path, what)?;
+        check_field(map, "tags", Kind::StrList, true, path, what)?;
+        check_field(map, "when", Kind::Condition, false, path, what)?;
+        Ok(())
+    } else if has("group") || has("inputs") {
+        diagnose_group(map, path)
+    } else if has("id") || has("label") || has("type") || has("options") {
+        diagnose_input(map, path)
+    } else {
+        Err(Mismatch::new(path, "couldn't work out what kind of element this is: expected text (a string), a progression (a map with `text`, `link`, and `tags`), an input (a map with `id` and `label`), or a group of inputs (a map with `group`, `label`, and `inputs`)"))
+    }
+}
+
+fn diagnose_group(map: &Mapping, path: &[Seg]) -> Result<(), Mismatch> {
+    let what = "a group of inputs";
+    check_field(map, "group", Kind::Str, true, path, what)?;
+    check_field(map, "label", Kind::Str, true, path, what)?;
+    check_field(map, "add_text", Kind::Str, false, path, what)?;
+    check_field(map, "remove_text", Kind::Str, false, path, what)?;
+    check_field(map, "when", Kind::Condition, false, path, what)?;
+    for key in ["min", "max"] {
+        if let Some(count) = get(map, key) {
+            check_as::<usize>(count, &join(path, Seg::Key(key.to_string())))?;
+        }
+    }
+    let path = join(path, Seg::Key("inputs".to_string()));
+    match get(map, "inputs") {
+        Some(Value::Sequence(inputs)) => {
+            for (i, input) in inputs.iter().enumerate() {
+                let path = join(&path, Seg::Index(i));
+                match input {
+                    Value::Mapping(input) => diagnose_input(input, &path)?,
+                    _ => {
+                        return Err(Mismatch::new(
+                            &path,
+                            "each input in a group must be a map with `id` and `label`",
+                        ))
+                    }
+                }
+            }
+            Ok(())
+        }
+        _ => Err(Mismatch::new(
+            &path,
+            "this looks like a group of inputs, but `inputs` must be a list of inputs",
+        )),
+    }
+}
+
+fn diagnose_input(map: &Mapping, path: &[Seg]) -> Result<(), Mismatch> {
+    let what = "an input";
+    check_field(map, "id", Kind::Str, true, path, what)?;
+    check_field(map, "label", Kind::Str, true, path, what)?;
+    check_field(map, "description", Kind::Str, false, path, what)?;
+    check_field(map, "hint", Kind::Str, false, path, what)?;
+    check_field(map, "example", Kind::Str, false, path, what)?;
+    check_field(map, "optional", Kind::Bool, false, path, what)?;
+    check_field(map, "sensitive", Kind::Bool, false, path, what)?;
+    // Inputs that let the user select multiple options can have a list of defaults
+    match get(map, "default") {
+        Some(Value::Sequence(_)) => check_field(map, "default", Kind::StrList, false, path, what)?,
+        _ => check_field(map, "default", Kind::Str, false, path, what)?,
+    }
+    check_field(map, "when", Kind::Condition, false, path, what)?;
+    if let Some(validate) = get(map, "validate") {
+        check_as::<Vec<crate::validation::Validation>>(
+            validate,
+            &join(path, Seg::Key("validate".to_string())),
+        )?;
+    }
+    if let Some(tag_rules) = get(map, "tag_rules") {
+        check_as::<Vec<crate::validation::TagRule>>(
+            tag_rules,
+            &join(path, Seg::Key("tag_rules".to_string())),
+        )?;
+    }
+    // Inputs with options are distinguished by the key their options are under
+    if let Some(options) = get(map, "options") {
+        check_field(
+            map,
+            "can_select_multiple",
+            Kind::Bool,
+            false,
+            path,
+            "a select input",
+        )?;
+        diagnose_options(options, "options", "a select input", path)
+    } else if let Some(options) = get(map, "radio") {
+        diagnose_options(options, "radio", "a radio group", path)
+    } else if let Some(options) = get(map, "checkboxes") {
+        diagnose_options(options, "checkboxes", "a checkbox group", path)
+    } else {
+        // The input type is an internally-tagged enum, so Serde's errors are fine here
+        check_as::<crate::parser::InputType>(&Value::Mapping(map.clone()), path).map_err(
+            |Mismatch { path, msg }| {
+                Mismatch::new(&path, format!("this looks like a text input, but {}", msg))
+            },
+        )
+    }
+}
+
+fn diagnose_options(options: &Value, key: &str, what: &str, path: &[Seg]) -> Result<(), Mismatch> {
+    let path = join(path, Seg::Key(key.to_string()));
+    let options = match options {
+        Value::Sequence(options) => options,
+        // The options are loaded from a data file, which is checked when it's loaded
+        Value::Mapping(_) => return check_as::<crate::parser::OptionsFile>(options, &path),
+        _ => {
+            return Err(Mismatch::new(
+                &path,
+                format!(
+                    "this looks like {}, but `{}` must be a list (or a reference to a file with `file`)",
+                    what, key
+                ),
+            ))
+        }
+    };
+    for (i, opt) in options.iter().enumerate() {
+        let path = join(&path, Seg::Index(i));
+        match opt {
+            Value::String(_) => (),
+            Value::Mapping(opt) => {
+                let what = "an option with tags";
+                check_field(opt, "text", Kind::Str, true, &path, what)?;
+                check_field(opt, "tags", Kind::StrList, true, &path, what)?;
+            }
+            _ => {
+                return Err(Mismatch::new(
+                    &path,
+                    "an option must either be a string or a map with `text` and `tags`",
+                ))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn diagnose_endpoint(value: &Value, path: &[Seg]) -> Result<(), Mismatch> {
+    let map = match value {
+        Value::String(_) => return Ok(()),
+        Value::Mapping(map) => map,
+        _ => return Err(Mismatch::new(path, "expected an instructional endpoint (a string) or a report endpoint (a map with `preamble`, `text`, and `dest_text`)")),
+    };
+    let what = "a report endpoint";
+    check_field(map, "preamble", Kind::Template, true, path, what)?;
+    check_field(map, "text", Kind::Template, true, path, what)?;
+    check_field(map, "dest_text", Kind::Str, true, path, what)?;
+    check_field(map, "dest_url", Kind::Template, false, path, what)?;
+    if let Some(max_url_len) = get(map, "max_url_len") {
+        check_as::<usize>(
+            max_url_len,
+            &join(path, Seg::Key("max_url_len".to_string())),
+        )?;
+    }
+    check_field(map, "copied_msg", Kind::Str, false, path, what)?;
+    check_field(map, "copy_failed_msg", Kind::Str, false, path, what)?;
+    if let Some(github) = get(map, "github") {
+        check_as::<crate::parser::GithubDest>(github, &join(path, Seg::Key("github".to_string())))?;
+    }
+    if let Some(gitlab) = get(map, "gitlab") {
+        check_as::<crate::parser::GitlabDest>(gitlab, &join(path, Seg::Key("gitlab".to_string())))?;
+    }
+    if let Some(gitea) = get(map, "gitea") {
+        check_as::<crate::parser::GiteaDest>(gitea, &join(path, Seg::Key("gitea".to_string())))?;
+    }
+    if let Some(submit) = get(map, "submit") {
+        check_as::<crate::parser::ReportSubmit>(
+            submit,
+            &join(path, Seg::Key("submit".to_string())),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Works out where every node in the given YAML document is, producing a map of formatted paths to line/column pairs (both starting from
+/// 1). This uses the low-level parser, because Serde throws away location information once a document has been parsed.
+fn locate(contents: &str) -> HashMap<String, (usize, usize)> {
+    let mut locator = Locator::default();
+    let mut parser = Parser::new(contents.chars());
+    // We only get here if the document was already parsed successfully, so this shouldn't fail (and if it does, we just won't have locations)
+    let _ = parser.load(&mut locator, false);
+    locator.locations
+}
+
+/// The containers we can be inside while walking through YAML events.
+enum Container {
+    /// A mapping, with the key we're waiting to see the value for (if we've just read a key).
+    Map(Vec<Seg>, Option<String>),
+    /// A sequence, with the index of the next element.
+    Seq(Vec<Seg>, usize),
+}
+
+#[derive(Default)]
+struct Locator {
+    stack: Vec<Container>,
+    locations: HashMap<String, (usize, usize)>,
+}
+impl Locator {
+    /// Registers the start of a new node, returning its path if it's a value (rather than a mapping key).
+    fn node(&mut self, scalar: Option<&str>, mark: Marker) -> Option<Vec<Seg>> {
+        let path = match self.stack.last_mut() {
+            Some(Container::Map(path, key)) => match key.take() {
+                Some(key) => join(path, Seg::Key(key)),
+                // This is a key, not a value (complex keys won't ever be used by Tribble, so we don't bother with them)
+                None => {
+                    *key = Some(scalar.unwrap_or("?").to_string());
+                    // Block mappings are marked as starting at their first colon, so we point to their first key instead
+                    let loc = (mark.line(), mark.col() + 1);
+                    let map_loc = self.locations.entry(fmt_path(path)).or_insert(loc);
+                    if loc < *map_loc {
+                        *map_loc = loc;
+                    }
+                    return None;
+                }
+            },
+            Some(Container::Seq(path, idx)) => {
+                *idx += 1;
+                join(path, Seg::Index(*idx - 1))
+            }
+            None => Vec::new(),
+        };
+        self.locations
+            .insert(fmt_path(&path), (mark.line(), mark.col() + 1));
+        Some(path)
+    }
+}
+impl MarkedEventReceiver for Locator {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        match ev {
+            Event::Scalar(value, ..) => {
+                self.node(Some(&value), mark);
+            }
+            Event::Alias(_) => {
+                self.node(None, mark);
+            }
+            Event::MappingStart(_) => {
+                let path = self.node(None, mark).unwrap_or_default();
+                self.stack.push(Container::Map(path, None));
+            }
+            Event::SequenceStart(_) => {
+                let path = self.node(None, mark).unwrap_or_default();
+                self.stack.push(Container::Seq(path, 0));
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Renders an excerpt of the file around the given location, with a caret pointing to the exact column.
+fn render_snippet(contents: &str, (line, col): (usize, usize)) -> String {
+    let lines: Vec<&str> = contents.lines().collect();
+    // We show one line of context either side
+    let first = line.saturating_sub(1).max(1);
+    let last = (line + 1).min(lines.len());
+    let gutter_width = last.to_string().len();
+
+    let mut snippet = format!("{} |", " ".repeat(gutter_width));
+    for line_num in first..=last {
+        let text = lines.get(line_num - 1).copied().unwrap_or_default();
+        snippet.push_str(&format!(
+            "\n{:>width$} | {}",
+            line_num,
+            text,
+            width = gutter_width
+        ));
+        if line_num == line {
+            snippet.push_str(&format!(
+                "\n{} | {}^",
+                " ".repeat(gutter_width),
+                " ".repeat(col.saturating_sub(1))
+            ));
+        }
+    }
+
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Config;
+
+    /// Parses the given (broken) language config file and diagnoses the error.
+    fn diagnose(contents: &str) -> Diagnostic {
+        let err =
+            serde_yaml::from_str::<Config>(contents).expect_err("config should have been invalid");
+        Diagnostic::new("en-US.yml", contents, err)
+    }
+
+    const BROKEN_PROGRESSION: &str = r#"workflows:
+  test:
+    title: Test
+    index: Start
+    sections:
+      Start:
+        - Some text.
+        - text: Next
+          link: "Report Bug"
+      "Report Bug":
+        - id: name
+          label: Name
+          optional: "yes"
+        - text: Done
+          link: endpoint:Done
+          tags: []
+    endpoints:
+      Done: Thanks!
+"#;
+
+    #[test]
+    fn guesses_progression_with_missing_field() {
+        let diagnostic = diagnose(BROKEN_PROGRESSION);
+        assert_eq!(
+            diagnostic.msg,
+            "this looks like a progression, but the required field `tags` is missing"
+        );
+        assert_eq!(
+            diagnostic.path.as_deref(),
+            Some("workflows.test.sections.Start[1]")
+        );
+        assert_eq!(diagnostic.location, Some((8, 11)));
+    }
+
+    #[test]
+    fn guesses_input_with_mistyped_field() {
+        let contents = BROKEN_PROGRESSION.replace(
+            "link: \"Report Bug\"",
+            "link: \"Report Bug\"\n          tags: []",
+        );
+        let diagnostic = diagnose(&contents);
+        assert_eq!(
+            diagnostic.msg,
+            "this looks like an input, but the field `optional` must be `true` or `false`"
+        );
+        assert_eq!(
+            diagnostic.path.as_deref(),
+            Some("workflows.test.sections.\"Report Bug\"[0].optional")
+        );
+        assert_eq!(diagnostic.location, Some((14, 21)));
+        let snippet = diagnostic.snippet.unwrap();
+        assert!(snippet.contains("14 |           optional: \"yes\"\n   |                     ^"));
+    }
+
+    #[test]
+    fn reports_invalid_conditions() {
+        let contents = BROKEN_PROGRESSION
+            .replace("optional: \"yes\"", "when: \"name ==\"")
+            .replace(
+                "link: \"Report Bug\"",
+                "link: \"Report Bug\"\n          tags: []",
+            );
+        let diagnostic = diagnose(&contents);
+        assert!(diagnostic.msg.starts_with("this looks like an input, but "));
+        assert_eq!(
+            diagnostic.path.as_deref(),
+            Some("workflows.test.sections.\"Report Bug\"[0].when")
+        );
+    }
+
+    #[test]
+    fn falls_back_to_raw_error_for_invalid_yaml() {
+        let contents = "workflows:\n  test:\n    title: [Test\n";
+        let diagnostic = diagnose(contents);
+        assert_eq!(diagnostic.path, None);
+        let (line, _) = diagnostic
+            .location
+            .expect("raw error should have a location");
+        assert!(line >= 3);
+        assert!(diagnostic.snippet.is_some());
+    }
+
+    #[test]
+    fn rejects_files_of_neither_kind() {
+        let diagnostic = diagnose("title: Test\n");
+        assert_eq!(diagnostic.path.as_deref(), Some(""));
+        assert!(diagnostic
+            .msg
+            .starts_with("expected either a `languages` map"));
+    }
+
+    #[test]
+    fn locates_nested_nodes() {
+        let locations = locate("a:\n  b:\n    - x\n    - \"y z\": 1\n");
+        assert_eq!(locations.get("a"), Some(&(2, 3)));
+        assert_eq!(locations.get("a.b[0]"), Some(&(3, 7)));
+        assert_eq!(locations.get("a.b[1].\"y z\""), Some(&(4, 14)));
+    }
+
+    #[test]
+    fn renders_snippet_with_context() {
+        let contents = "one\ntwo\nthree\n";
+        assert_eq!(
+            render_snippet(contents, (2, 2)),
+            "  |\n1 | one\n2 | two\n  |  ^\n3 | three"
+        );
+        // There's no line before the first
+        assert_eq!(
+            render_snippet(contents, (1, 1)),
+            "  |\n1 | one\n  | ^\n2 | two"
+        );
+    }
+}
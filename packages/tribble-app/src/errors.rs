@@ -1,3 +1,4 @@
+use crate::diagnostics::Diagnostic;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,16 +9,51 @@ pub enum ParserError {
         #[source]
         source: std::io::Error,
     },
-    #[error("parsing error occurred while attempting to deserialize config at '{filename}'")]
+    // The diagnostic already includes everything useful from the underlying Serde error, so we don't chain that as a source
+    // It's boxed because it's much larger than any other error, and it would make every `Result` with this error that large too
+    #[error("parsing error occurred while attempting to deserialize config at '{filename}': {diagnostic}")]
     ParseRawError {
         filename: String,
-        #[source]
-        source: serde_yaml::Error,
+        diagnostic: Box<Diagnostic>,
     },
     #[error("the root config file at '{filename}' did not define any languages (you must define at least one)")]
     NoLanguages { filename: String },
     #[error("the root config file at '{filename}' linked to another root config file at '{linked}', but root config files can only link to language config files")]
     RootLinksToRoot { filename: String, linked: String },
+    #[error("the root config file at '{filename}' set the reference locale to '{locale}', but that isn't one of its languages")]
+    NoSuchReferenceLocale { filename: String, locale: String },
+    #[error("the root config file at '{filename}' doesn't define the language '{locale}'")]
+    NoSuchLocale { filename: String, locale: String },
+    #[error("the workflow '{workflow}' from the reference locale is missing in one of the languages defined in '{filename}' (run `tribble check` for details)")]
+    MissingTranslatedWorkflow { filename: String, workflow: String },
+    #[error("the default value of the input '{input}' in the workflow '{workflow}' in '{filename}' breaks its own validation rules ({msg})")]
+    InvalidDefault {
+        filename: String,
+        workflow: String,
+        input: String,
+        msg: String,
+    },
+    #[error("couldn't read the options file '{file}' referenced in '{filename}'")]
+    OptionsFileFsError {
+        filename: String,
+        file: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("the options file '{file}' referenced in '{filename}' is invalid: {msg}")]
+    InvalidOptionsFile {
+        filename: String,
+        file: String,
+        msg: String,
+    },
+    #[error("the `{field}` of the report endpoint '{endpoint}' in the workflow '{workflow}' in '{filename}' references the input '{id}', but no input with that id can be reached before that endpoint (run `tribble check` for details)")]
+    UnreachableTemplateRef {
+        filename: String,
+        workflow: String,
+        endpoint: String,
+        field: String,
+        id: String,
+    },
 }
 #[derive(Error, Debug)]
 pub enum ExportError {
@@ -37,3 +73,39 @@ pub enum ExportError {
         source: perseus::errors::GlobalStateError,
     },
 }
+#[derive(Error, Debug)]
+pub enum ConditionError {
+    #[error("unexpected end of condition '{cond}'")]
+    UnexpectedEnd { cond: String },
+    #[error("unexpected '{token}' in condition '{cond}'")]
+    UnexpectedToken { cond: String, token: String },
+    #[error("unterminated string in condition '{cond}'")]
+    UnterminatedString { cond: String },
+    #[error("invalid number '{num}' in condition '{cond}' (if this is meant to be text, put it in quotes)")]
+    InvalidNumber { cond: String, num: String },
+    #[error("`tags` can only be used as `tags contains \"<tag>\"` (in condition '{cond}')")]
+    InvalidTagsUsage { cond: String },
+}
+#[derive(Error, Debug)]
+pub enum TemplateError {
+    #[error("unterminated tag starting with '{tag}'")]
+    UnterminatedTag { tag: String },
+    #[error("invalid tag '${{{tag}}}' (expected `${{id}}` or `${{id | filter}}`)")]
+    InvalidTag { tag: String },
+    #[error("unknown filter '{filter}' in tag '${{{tag}}}' (expected `upper`, `code`, `quote`, `list`, or `default(\"...\")`)")]
+    UnknownFilter { filter: String, tag: String },
+    #[error("invalid use of filter '{filter}' in tag '${{{tag}}}' (only `default` takes an argument, which must be a string)")]
+    InvalidFilterArgs { filter: String, tag: String },
+    #[error("invalid block '{{% {block} %}}' (expected `if <condition>`, `for <item> in tags`, `for <item> in ${{id}}`, `else`, or `end`)")]
+    InvalidBlock { block: String },
+    #[error("unexpected '{{% {block} %}}' without a matching `if` or `for`")]
+    UnexpectedBlock { block: String },
+    #[error("'{{% {block} %}}' is never closed with '{{% end %}}'")]
+    UnclosedBlock { block: String },
+    #[error("invalid condition in '{{% {block} %}}': {source}")]
+    InvalidCondition {
+        block: String,
+        #[source]
+        source: ConditionError,
+    },
+}
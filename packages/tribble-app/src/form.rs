@@ -0,0 +1,284 @@
+use crate::parser::{DefaultValue, Input, InputSectionElem, InputType};
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+use std::fmt;
+
+/// The value of an input, typed according to the kind of input it came from. This is what's used for conditions, tag accumulation, and
+/// report templates, so options can contain any text at all (including commas).
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum FormValue {
+    /// A single piece of text (from a text input, a select that only allows one option, or a radio group).
+    Single(String),
+    /// A list of selected options (from a select that allows multiple options, or a checkbox group), in the order they were defined in.
+    List(Vec<String>),
+    /// Whether or not a boolean input was checked.
+    Boolean(bool),
+    /// A number (from a number or range input), which won't be present if the user hasn't entered a valid one.
+    Number(Option<f64>),
+    /// Some code or logs from a code input, along with how that input wants them shown in reports.
+    Code {
+        /// The code itself.
+        text: String,
+        /// The language the code should be highlighted as.
+        language: Option<String>,
+        /// The maximum number of lines to show before cutting the code short.
+        #[serde(skip)]
+        max_lines: Option<usize>,
+        /// The text of the toggle that reveals the full code if it's cut short.
+        #[serde(skip)]
+        truncated_msg: String,
+    },
+    /// The entries the user has filled out in a group of inputs.
+    Group(Vec<GroupEntry>),
+    /// A date and/or time, in the format the browser gives us (e.g. `2022-01-01` or `12:30`). These are always zero-padded, so they can
+    /// be compared as text.
+    Date(String),
+}
+impl FormValue {
+    /// Parses the raw text of the given input (what the browser gives us) into a value of the right type for that input.
+    pub fn from_text(input: &Input, text: &str) -> Self {
+        match input {
+            Input::Text { input_type } => match input_type {
+                InputType::Boolean { .. } => Self::Boolean(text == "true"),
+                InputType::Code {
+                    language,
+                    max_lines,
+                    truncated_msg,
+                } => Self::Code {
+                    text: text.to_string(),
+                    language: language
+                        .as_ref()
+                        .map(|language| language.as_str().to_string()),
+                    max_lines: *max_lines,
+                    truncated_msg: truncated_msg.to_string(),
+                },
+                InputType::Number { .. } | InputType::Range { .. } => {
+                    Self::Number(text.trim().parse().ok())
+                }
+                InputType::Date
+                | InputType::DatetimeLocal
+                | InputType::Month
+                | InputType::Time
+                | InputType::Week => Self::Date(text.to_string()),
+                _ => Self::Single(text.to_string()),
+            },
+            input if input.is_multiple() => Self::List(if text.is_empty() {
+                Vec::new()
+            } else {
+                vec![text.to_string()]
+            }),
+            _ => Self::Single(text.to_string()),
+        }
+    }
+    /// Rebuilds a value for the given input from the JSON it was serialized to (which is how progress is saved in the browser), returning
+    /// `None` if it doesn't fit the input. Values from code inputs get their settings back from the input.
+    pub fn from_saved(input: &Input, saved: &serde_json::Value) -> Option<Self> {
+        match saved {
+            serde_json::Value::Array(items) if input.is_multiple() => items
+                .iter()
+                .map(|item| item.as_str().map(|item| item.to_string()))
+                .collect::<Option<Vec<String>>>()
+                .map(Self::List),
+            serde_json::Value::String(text) => Some(Self::from_text(input, text)),
+            serde_json::Value::Bool(checked) => Some(Self::from_text(input, &checked.to_string())),
+            serde_json::Value::Number(num) => Some(Self::from_text(input, &num.to_string())),
+            // A number input that didn't have a valid number in it
+            serde_json::Value::Null => Some(Self::from_text(input, "")),
+            serde_json::Value::Object(map) => map
+                .get("text")
+                .and_then(|text| text.as_str())
+                .map(|text| Self::from_text(input, text)),
+            _ => None,
+        }
+    }
+    /// Creates a value for the given input from its default in the configuration.
+    pub fn from_default(input: &Input, default: &DefaultValue) -> Self {
+        match default {
+            DefaultValue::List(items) if input.is_multiple() => Self::List(items.clone()),
+            DefaultValue::Single(text) => Self::from_text(input, text),
+            // An input that only has one value can only use the first of a list of defaults
+            DefaultValue::List(items) => {
+                Self::from_text(input, items.first().map(|s| s.as_str()).unwrap_or(""))
+            }
+        }
+    }
+    /// Creates the value the given input starts with, which is its default if it has one.
+    pub fn from_input(input: &InputSectionElem) -> Self {
+        match &input.default {
+            Some(default) => Self::from_default(&input.input, default),
+            None => Self::from_text(&input.input, ""),
+        }
+    }
+    /// Checks if the user hasn't entered anything. Booleans always have a value (even if it's `false`).
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Single(text) | Self::Date(text) | Self::Code { text, .. } => text.is_empty(),
+            Self::List(items) => items.is_empty(),
+            Self::Group(entries) => entries.is_empty(),
+            Self::Boolean(_) => false,
+            Self::Number(num) => num.is_none(),
+        }
+    }
+    /// Checks if this value counts as true in a condition, which it does if it's not empty (and it's not a `false` boolean).
+    pub fn is_truthy(&self) -> bool {
+        !self.is_empty() && self != &Self::Boolean(false)
+    }
+    /// Gets the individual items in this value. A list has each of its items, a group has each of its entries, and anything else that
+    /// isn't empty has only itself.
+    pub fn items(&self) -> Vec<String> {
+        match self {
+            Self::List(items) => items.clone(),
+            Self::Group(entries) => entries.iter().map(|entry| entry.to_string()).collect(),
+            value if value.is_empty() => Vec::new(),
+            value => vec![value.to_string()],
+        }
+    }
+    /// Gets this value as a number, if it is one (or if it's text that can be parsed as one).
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(num) => *num,
+            Self::Single(text) => text.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+impl fmt::Display for FormValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Single(text) | Self::Date(text) | Self::Code { text, .. } => {
+                write!(f, "{}", text)
+            }
+            Self::List(items) => write!(f, "{}", items.join(", ")),
+            Self::Group(entries) => write!(
+                f,
+                "{}",
+                entries
+                    .iter()
+                    .map(|entry| entry.to_string())
+                    .collect::<Vec<String>>()
+                    .join("; ")
+            ),
+            Self::Boolean(val) => write!(f, "{}", val),
+            Self::Number(Some(num)) => write!(f, "{}", num),
+            Self::Number(None) => Ok(()),
+        }
+    }
+}
+
+/// One entry in a group of inputs, which holds the values of the inputs in it (in the order they were defined in).
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupEntry(pub Vec<(String, FormValue)>);
+impl GroupEntry {
+    /// Gets the value of the input with the given ID in this entry.
+    pub fn get(&self, id: &str) -> Option<&FormValue> {
+        self.0
+            .iter()
+            .find(|(input_id, _)| input_id == id)
+            .map(|(_, value)| value)
+    }
+}
+impl fmt::Display for GroupEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> = self
+            .0
+            .iter()
+            .map(|(_, value)| value.to_string())
+            .filter(|value| !value.is_empty())
+            .collect();
+        write!(f, "{}", values.join(", "))
+    }
+}
+// Entries are sent to receivers as maps of input IDs to values
+impl Serialize for GroupEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (id, value) in &self.0 {
+            map.serialize_entry(id, value)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn input(yaml: &str) -> Input {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+    /// Saves the given value and loads it again for the given input, just like progress is saved in the browser.
+    fn round_trip(input: &Input, value: FormValue) -> Option<FormValue> {
+        FormValue::from_saved(input, &serde_json::to_value(&value).unwrap())
+    }
+
+    #[test]
+    fn saved_values_round_trip() {
+        let text = input("type: text");
+        assert_eq!(
+            round_trip(&text, FormValue::Single("It broke".to_string())),
+            Some(FormValue::Single("It broke".to_string()))
+        );
+        let boolean = input("type: boolean");
+        assert_eq!(
+            round_trip(&boolean, FormValue::Boolean(true)),
+            Some(FormValue::Boolean(true))
+        );
+        let number = input("type: number");
+        assert_eq!(
+            round_trip(&number, FormValue::Number(Some(2.5))),
+            Some(FormValue::Number(Some(2.5)))
+        );
+        assert_eq!(
+            round_trip(&number, FormValue::Number(None)),
+            Some(FormValue::Number(None))
+        );
+        let date = input("type: date");
+        assert_eq!(
+            round_trip(&date, FormValue::Date("2022-01-01".to_string())),
+            Some(FormValue::Date("2022-01-01".to_string()))
+        );
+        let checkboxes = input("checkboxes: [ \"Frontend, mostly\", \"Backend\" ]");
+        let areas = FormValue::List(vec!["Frontend, mostly".to_string(), "Backend".to_string()]);
+        assert_eq!(round_trip(&checkboxes, areas.clone()), Some(areas));
+    }
+    #[test]
+    fn saved_code_gets_settings_from_input() {
+        let code = input("type: code\nlanguage: console\nmax_lines: 30");
+        assert_eq!(
+            FormValue::from_saved(
+                &code,
+                &json!({ "text": "panicked at 'oops'", "language": "rust" })
+            ),
+            Some(FormValue::Code {
+                text: "panicked at 'oops'".to_string(),
+                language: Some("console".to_string()),
+                max_lines: Some(30),
+                truncated_msg: "Full output".to_string(),
+            })
+        );
+        assert_eq!(
+            FormValue::from_saved(&code, &json!({ "language": "rust" })),
+            None
+        );
+    }
+    #[test]
+    fn saved_values_that_dont_fit_are_rejected() {
+        let checkboxes = input("checkboxes: [ \"Frontend\", \"Backend\" ]");
+        assert_eq!(
+            FormValue::from_saved(&checkboxes, &json!(["Frontend", 1])),
+            None
+        );
+        // Lists are only kept for inputs that allow multiple options
+        assert_eq!(
+            FormValue::from_saved(&input("type: text"), &json!(["Frontend"])),
+            None
+        );
+        // A single option becomes a list of one for an input that allows multiple
+        assert_eq!(
+            FormValue::from_saved(&checkboxes, &json!("Frontend")),
+            Some(FormValue::List(vec!["Frontend".to_string()]))
+        );
+    }
+}
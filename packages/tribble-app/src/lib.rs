@@ -1,8 +1,16 @@
+pub mod check;
+pub mod condition;
+pub mod diagnostics;
 mod error_pages;
 pub mod errors;
+pub mod form;
+pub mod option_files;
 pub mod parser;
+pub mod report;
 mod svg;
+pub mod template;
 mod templates;
+pub mod validation;
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "export"))]
 mod export;
@@ -16,6 +24,7 @@ use perseus_size_opt::{perseus_size_opt, SizeOpts};
 #[perseus::main]
 pub fn main<G: Html>() -> PerseusApp<G> {
     PerseusApp::new()
+        .template(templates::index::get_template)
         .template(templates::workflow::get_template)
         .error_pages(error_pages::get_error_pages)
         .plugins(Plugins::new().plugin(perseus_size_opt, SizeOpts::default_2018()))
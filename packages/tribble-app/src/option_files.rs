@@ -0,0 +1,256 @@
+use crate::errors::ParserError;
+use crate::parser::{OptionSource, OptionsFile, SectionElem, SelectOption, Workflow};
+use serde_yaml::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Loads the options of every input in the given workflow whose options come from a data file, replacing the references to those files
+/// with the options themselves. Files are found relative to the given configuration file (which the workflow came from).
+pub fn resolve_option_files(filename: &str, workflow: &mut Workflow) -> Result<(), ParserError> {
+    for section in workflow.sections.values_mut() {
+        for elem in section.iter_mut() {
+            if let SectionElem::Input(input) = elem {
+                if let Some(source) = input.input.option_source_mut() {
+                    if let OptionSource::File(file) = source {
+                        let options = load_options(filename, file)?;
+                        *source = OptionSource::Inline(options);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets the paths to all the data files that the options of inputs in the given workflow come from (so they can be watched for changes),
+/// resolved relative to the given configuration file.
+pub fn option_files(filename: &str, workflow: &Workflow) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = workflow
+        .sections
+        .values()
+        .flatten()
+        .filter_map(|elem| match elem {
+            SectionElem::Input(input) => match input.input.option_source() {
+                Some(OptionSource::File(file)) => Some(resolve_path(filename, &file.file)),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    // The same file is often used by more than one input
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Resolves the given path relative to the directory of the given configuration file.
+fn resolve_path(filename: &str, file: &str) -> PathBuf {
+    match Path::new(filename).parent() {
+        Some(dir) => dir.join(file),
+        None => PathBuf::from(file),
+    }
+}
+
+/// Loads the options from the given data file, which is referenced in the given configuration file.
+fn load_options(filename: &str, source: &OptionsFile) -> Result<Vec<SelectOption>, ParserError> {
+    let invalid = |msg: String| ParserError::InvalidOptionsFile {
+        filename: filename.to_string(),
+        file: source.file.clone(),
+        msg,
+    };
+    let path = resolve_path(filename, &source.file);
+    let contents = fs::read_to_string(&path).map_err(|err| ParserError::OptionsFileFsError {
+        filename: filename.to_string(),
+        file: source.file.clone(),
+        source: err,
+    })?;
+    let options = match path.extension().and_then(|ext| ext.to_str()) {
+        // JSON is valid YAML, so the same parser handles both
+        Some("yml" | "yaml" | "json") => options_from_yaml(&contents, source),
+        Some("csv") => options_from_csv(&contents, source),
+        _ => Err("options files must be YAML, JSON, or CSV (with a `.yml`, `.yaml`, `.json`, or `.csv` extension)".to_string()),
+    }
+    .map_err(invalid)?;
+    if options.is_empty() {
+        return Err(invalid("there aren't any options in this file".to_string()));
+    }
+
+    Ok(options)
+}
+
+/// Parses options from a YAML or JSON list, each element of which is either the text of an option or a map with its text and tags.
+fn options_from_yaml(contents: &str, source: &OptionsFile) -> Result<Vec<SelectOption>, String> {
+    let items: Vec<Value> = serde_yaml::from_str(contents)
+        .map_err(|err| format!("couldn't parse a list of options ({})", err))?;
+    let text_key = Value::String(source.text.clone());
+    let tags_key = Value::String(source.tags.clone());
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| match item {
+            Value::String(text) => Ok(SelectOption::Simple(text.to_string())),
+            Value::Mapping(map) => {
+                let text = match map.get(&text_key) {
+                    Some(Value::String(text)) => text.to_string(),
+                    _ => {
+                        return Err(format!(
+                            "option {} doesn't have a `{}` field that's a string",
+                            i + 1,
+                            source.text
+                        ))
+                    }
+                };
+                let tags = match map.get(&tags_key) {
+                    None | Some(Value::Null) => Vec::new(),
+                    Some(tags) => serde_yaml::from_value(tags.clone()).map_err(|_| {
+                        format!(
+                            "the `{}` field of option {} must be a list of strings",
+                            source.tags,
+                            i + 1
+                        )
+                    })?,
+                };
+                Ok(SelectOption::WithTags { text, tags })
+            }
+            _ => Err(format!(
+                "option {} must either be a string or a map with its text and tags",
+                i + 1
+            )),
+        })
+        .collect()
+}
+
+/// Parses options from a CSV file with a header row, where each row is an option. The tags column is optional, and tags in it are separated
+/// by spaces.
+fn options_from_csv(contents: &str, source: &OptionsFile) -> Result<Vec<SelectOption>, String> {
+    let mut reader = csv::Reader::from_reader(contents.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|err| format!("couldn't read the header row ({})", err))?
+        .clone();
+    let text_col = headers
+        .iter()
+        .position(|header| header == source.text)
+        .ok_or_else(|| format!("there's no `{}` column", source.text))?;
+    let tags_col = headers.iter().position(|header| header == source.tags);
+
+    reader
+        .records()
+        .enumerate()
+        .map(|(i, record)| {
+            // The header is the first line
+            let record = record.map_err(|err| format!("couldn't read row {} ({})", i + 2, err))?;
+            let text = record.get(text_col).unwrap_or("").trim().to_string();
+            if text.is_empty() {
+                return Err(format!("row {} doesn't have any text", i + 2));
+            }
+            let tags: Vec<String> = tags_col
+                .and_then(|col| record.get(col))
+                .unwrap_or("")
+                .split_whitespace()
+                .map(|tag| tag.to_string())
+                .collect();
+            Ok(SelectOption::WithTags { text, tags })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(file: &str) -> OptionsFile {
+        OptionsFile {
+            file: file.to_string(),
+            text: "text".to_string(),
+            tags: "tags".to_string(),
+        }
+    }
+    fn flatten(options: &[SelectOption]) -> Vec<(&str, Vec<&str>)> {
+        options
+            .iter()
+            .map(|opt| {
+                (
+                    opt.text(),
+                    opt.tags().iter().map(|tag| tag.as_str()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loads_yaml_and_json_options() {
+        let yaml = "- Linux\n- text: macOS\n  tags: [ \"os:mac\", apple ]\n- text: Windows\n";
+        let options = options_from_yaml(yaml, &source("os.yml")).unwrap();
+        assert_eq!(
+            flatten(&options),
+            vec![
+                ("Linux", vec![]),
+                ("macOS", vec!["os:mac", "apple"]),
+                ("Windows", vec![]),
+            ]
+        );
+        let json = r#"[ "Linux", { "text": "macOS", "tags": [ "os:mac" ] } ]"#;
+        let options = options_from_yaml(json, &source("os.json")).unwrap();
+        assert_eq!(
+            flatten(&options),
+            vec![("Linux", vec![]), ("macOS", vec!["os:mac"])]
+        );
+    }
+    #[test]
+    fn uses_custom_yaml_fields() {
+        let source = OptionsFile {
+            text: "name".to_string(),
+            tags: "labels".to_string(),
+            ..source("os.yml")
+        };
+        let options = options_from_yaml("- name: Linux\n  labels: [ os ]\n", &source).unwrap();
+        assert_eq!(flatten(&options), vec![("Linux", vec!["os"])]);
+        // The default field names don't work anymore
+        assert!(options_from_yaml("- text: Linux\n", &source).is_err());
+    }
+    #[test]
+    fn rejects_invalid_yaml_options() {
+        let err = |yaml: &str| options_from_yaml(yaml, &source("os.yml")).unwrap_err();
+        assert!(err("text: Linux").contains("list of options"));
+        assert!(err("- Linux\n- tags: [ os ]\n").starts_with("option 2 "));
+        assert!(err("- text: Linux\n  tags: os\n").contains("list of strings"));
+        assert!(err("- Linux\n- [ macOS ]\n").starts_with("option 2 "));
+    }
+    #[test]
+    fn loads_csv_options() {
+        let csv = "text,tags\nLinux,os:linux unix\n\"Windows, sadly\",\nmacOS,\n";
+        let options = options_from_csv(csv, &source("os.csv")).unwrap();
+        assert_eq!(
+            flatten(&options),
+            vec![
+                ("Linux", vec!["os:linux", "unix"]),
+                ("Windows, sadly", vec![]),
+                ("macOS", vec![]),
+            ]
+        );
+        // The tags column is optional, and other columns are ignored
+        let csv = "id,text\n1,Linux\n";
+        let options = options_from_csv(csv, &source("os.csv")).unwrap();
+        assert_eq!(flatten(&options), vec![("Linux", vec![])]);
+    }
+    #[test]
+    fn rejects_invalid_csv_options() {
+        let err = |csv: &str| options_from_csv(csv, &source("os.csv")).unwrap_err();
+        assert_eq!(err("name,tags\nLinux,\n"), "there's no `text` column");
+        assert_eq!(
+            err("text,tags\nLinux,\n ,os\n"),
+            "row 3 doesn't have any text"
+        );
+        assert!(err("text,tags\nLinux,,extra\n").starts_with("couldn't read row 2 "));
+    }
+    #[test]
+    fn resolves_paths_relative_to_config() {
+        assert_eq!(
+            resolve_path("config/en.yml", "data/os.csv"),
+            Path::new("config").join("data/os.csv")
+        );
+        assert_eq!(resolve_path("en.yml", "os.csv"), PathBuf::from("os.csv"));
+    }
+}
@@ -1,14 +1,117 @@
+use crate::condition::Condition;
+use crate::diagnostics::Diagnostic;
 use crate::errors::ParserError;
+use crate::template::Template;
+use crate::validation::{TagRule, Validation};
 #[cfg(feature = "schema")]
-use schemars::JsonSchema;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fs::File, io::BufReader};
+use std::convert::TryFrom;
+use std::{collections::HashMap, fmt, fs};
 
 /// Gets the default error message when the user doesn't fill out a mandatory field.
 fn default_input_err_msg() -> String {
     "This field is required, please enter a value.".to_string()
 }
 
+/// Gets the default message shown when a report has been submitted successfully.
+fn default_submit_success_msg() -> String {
+    "Your report has been submitted, thank you!".to_string()
+}
+/// Gets the default message shown when a report couldn't be submitted.
+fn default_submit_err_msg() -> String {
+    "Your report couldn't be submitted, please try again.".to_string()
+}
+/// Gets the default message shown when a report is too long to be sent in a URL, which will also be sent in the report body's place. This
+/// is shown before the report has been copied (and the copy might fail), so it shouldn't say that it's already on the user's clipboard.
+fn default_copied_msg() -> String {
+    "This report was too long to fit in this link, so please paste it in here from your clipboard."
+        .to_string()
+}
+/// Gets the default message shown when something couldn't be copied to the user's clipboard, above the text they'll need to copy themselves.
+fn default_copy_failed_msg() -> String {
+    "Your browser wouldn't let us copy this, so please copy it from here:".to_string()
+}
+/// Gets the default number of entries a group of inputs has to have.
+fn default_group_min() -> usize {
+    1
+}
+/// Gets the default text of the button for adding an entry to a group of inputs.
+fn default_add_text() -> String {
+    "Add another".to_string()
+}
+/// Gets the default text of the button for removing an entry from a group of inputs.
+fn default_remove_text() -> String {
+    "Remove".to_string()
+}
+/// Gets the default field that the text of options loaded from a data file is taken from.
+fn default_text_field() -> String {
+    "text".to_string()
+}
+/// Gets the default field that the tags of options loaded from a data file are taken from.
+fn default_tags_field() -> String {
+    "tags".to_string()
+}
+/// Gets the default text of the toggle that reveals the full contents of a code input that was too long to show in a report.
+fn default_truncated_msg() -> String {
+    "Full output".to_string()
+}
+/// Gets the default message asking a user if they want to pick up a workflow where they left off.
+fn default_resume_msg() -> String {
+    "Welcome back! Would you like to pick up where you left off?".to_string()
+}
+/// Gets the default text of the button for picking up a workflow where the user left off.
+fn default_resume_text() -> String {
+    "Resume".to_string()
+}
+/// Gets the default text of the button for starting a workflow over instead of picking it up where the user left off.
+fn default_restart_text() -> String {
+    "Start over".to_string()
+}
+/// Gets the default text of the button for copying a link to the current step.
+fn default_share_text() -> String {
+    "Copy link to this step".to_string()
+}
+/// Gets the default text of the button for copying a link to the current step once the link has been copied.
+fn default_share_copied_text() -> String {
+    "Link copied!".to_string()
+}
+/// Gets the default text shown when a link to the current step couldn't be copied, above the link itself.
+fn default_share_copy_failed_text() -> String {
+    "The link couldn't be copied, so please copy it from here:".to_string()
+}
+/// Gets the default for whether or not links to a step include the values of inputs (`ShareLink::include_values`), which they do.
+fn default_true() -> bool {
+    true
+}
+/// Gets the default message shown when the user is sent to a section that doesn't exist.
+fn default_missing_section_msg() -> String {
+    "Sorry, there's no section called '%s' in this workflow. Please let the maintainers know!"
+        .to_string()
+}
+/// Gets the default message shown when the user is sent to an endpoint that doesn't exist.
+fn default_missing_endpoint_msg() -> String {
+    "Sorry, there's no endpoint called '%s' in this workflow. Please let the maintainers know!"
+        .to_string()
+}
+/// Gets the default text of the button for going back to the previous section from an error.
+fn default_back_text() -> String {
+    "Go back".to_string()
+}
+/// Gets the default title of the landing page.
+fn default_landing_title() -> String {
+    "Contribute".to_string()
+}
+/// Gets the default text shown above the list of workflows on the landing page.
+fn default_landing_text() -> String {
+    "How would you like to contribute?".to_string()
+}
+/// Gets the default maximum length of a URL to a report destination. Most forges sit behind proxies that reject request lines longer than
+/// about 8KiB, so we stay a little below that.
+fn default_max_url_len() -> usize {
+    8000
+}
+
 /// The possible types of configuration files (this allows main files to be different from internationalization files).
 // Note: Markdown is supported in three places: an instructional endpoint, the preamble of a report endpoint, and a text element in a section.
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
@@ -19,12 +122,25 @@ pub enum Config {
     Root {
         /// A map of the languages supported to filenames, a structure that separates each language into a separate Tribble file.
         languages: HashMap<String, String>,
+        /// The locale that all other languages are translated from, which `tribble check` will compare them against to make sure they all
+        /// have the same structure. If this isn't provided, the alphabetically first locale will be used.
+        #[serde(default)]
+        reference_locale: Option<String>,
     },
     /// A configuration file for a single language.
     Language {
         /// The error message when a user doesn't fill out a mandatory field. This is allowed to enable i18n at an arbitrary scale. This field does not support Markdown.
         #[serde(default = "default_input_err_msg")]
         input_err_msg: String,
+        /// The text of the prompt shown when a user comes back to a workflow they didn't finish, which offers to pick up where they left off.
+        #[serde(default)]
+        resume: ResumeText,
+        /// The text shown when a user is sent somewhere in a workflow that doesn't exist (which `tribble check` will catch beforehand).
+        #[serde(default)]
+        errors: ErrorText,
+        /// The text of the landing page, which lists all the workflows.
+        #[serde(default)]
+        landing: LandingText,
         /// All the workflow in this Tribble instance. Each workflow is a separate contribution experience, and multiple workflows are generally best suited for things like separate products.
         workflows: HashMap<String, Workflow>,
     },
@@ -32,19 +148,99 @@ pub enum Config {
 impl Config {
     /// Creates a new instance of the raw configuration from a file.
     pub fn new(filename: &str) -> Result<Self, ParserError> {
-        // We'll parse it directly from a reader for efficiency
-        let file = File::open(filename).map_err(|err| ParserError::FsError {
+        // We read the whole file in so that we can point to the exact location of any errors
+        let contents = fs::read_to_string(filename).map_err(|err| ParserError::FsError {
             filename: filename.to_string(),
             source: err,
         })?;
-        let reader = BufReader::new(file);
-        let contents: Self =
-            serde_yaml::from_reader(reader).map_err(|err| ParserError::ParseRawError {
+        let cfg: Self =
+            serde_yaml::from_str(&contents).map_err(|err| ParserError::ParseRawError {
                 filename: filename.to_string(),
-                source: err,
+                diagnostic: Box::new(Diagnostic::new(filename, &contents, err)),
             })?;
 
-        Ok(contents)
+        Ok(cfg)
+    }
+    /// Gets the locale that all other languages should be structurally identical to, if this is a root configuration file. This will be the
+    /// one explicitly specified, or the alphabetically first locale otherwise.
+    pub fn reference_locale(&self) -> Option<&str> {
+        match self {
+            Self::Root {
+                reference_locale: Some(locale),
+                ..
+            } => Some(locale),
+            Self::Root { languages, .. } => languages.keys().min().map(|locale| locale.as_str()),
+            Self::Language { .. } => None,
+        }
+    }
+}
+
+/// The text of the prompt shown when a user comes back to a workflow they didn't finish (their progress is saved in their browser). None of
+/// this supports Markdown.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResumeText {
+    /// The message asking the user if they want to pick up where they left off.
+    #[serde(default = "default_resume_msg")]
+    pub msg: String,
+    /// The text of the button for picking up where they left off.
+    #[serde(default = "default_resume_text")]
+    pub resume_text: String,
+    /// The text of the button for starting over (which discards their saved progress).
+    #[serde(default = "default_restart_text")]
+    pub restart_text: String,
+}
+impl Default for ResumeText {
+    fn default() -> Self {
+        Self {
+            msg: default_resume_msg(),
+            resume_text: default_resume_text(),
+            restart_text: default_restart_text(),
+        }
+    }
+}
+
+/// The text of the landing page, which lists all the workflows in a language.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LandingText {
+    /// The title of the page (appears in tabs).
+    #[serde(default = "default_landing_title")]
+    pub title: String,
+    /// Text shown above the list of workflows. Markdown is supported here.
+    #[serde(default = "default_landing_text")]
+    pub text: String,
+}
+impl Default for LandingText {
+    fn default() -> Self {
+        Self {
+            title: default_landing_title(),
+            text: default_landing_text(),
+        }
+    }
+}
+
+/// The text shown when a user is sent to a section or endpoint that doesn't exist. None of this supports Markdown.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorText {
+    /// The message shown for a section that doesn't exist, in which `%s` will be replaced with the name of the section.
+    #[serde(default = "default_missing_section_msg")]
+    pub missing_section: String,
+    /// The message shown for an endpoint that doesn't exist, in which `%s` will be replaced with the name of the endpoint.
+    #[serde(default = "default_missing_endpoint_msg")]
+    pub missing_endpoint: String,
+    /// The text of the button for going back to the previous section.
+    #[serde(default = "default_back_text")]
+    pub back_text: String,
+}
+impl Default for ErrorText {
+    fn default() -> Self {
+        Self {
+            missing_section: default_missing_section_msg(),
+            missing_endpoint: default_missing_endpoint_msg(),
+            back_text: default_back_text(),
+        }
     }
 }
 
@@ -52,14 +248,71 @@ impl Config {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Workflow {
-    /// The title of the page dedicated to this workflow (appears in tabs).
+    /// The title of the page dedicated to this workflow (appears in tabs). This is also what the workflow is called on the landing page.
     pub title: String,
+    /// A short description of what the workflow is for, shown under its title on the landing page. This does not support Markdown.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// An icon shown next to the workflow on the landing page, which can either be some text (like an emoji) or the URL of an image (anything
+    /// with a `/` in it is treated as a URL).
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Where the workflow should appear on the landing page, which lists workflows from the lowest `order` to the highest. Workflows without
+    /// an `order` come after all the others, and any ties are sorted by title.
+    #[serde(default)]
+    pub order: Option<i32>,
     /// The sections that the page can make use of.
     pub sections: HashMap<String, Section>,
     /// The section to start on, which must be a valid key in the `sections` map.
     pub index: String,
     /// The endpoints that the user can exit the process from.
     pub endpoints: HashMap<String, Endpoint>,
+    /// The inputs that links to this workflow can fill in, and the sections they can start at. Nothing can be prefilled unless it's
+    /// listed here.
+    #[serde(default)]
+    pub prefill: Prefill,
+    /// Values that the user never sees, but that can be used in report templates just like inputs (e.g. `version: "${major}.${minor}"`).
+    /// Each one is a template, so it can be a constant or built from the values of inputs and the built-in values `${_locale}`,
+    /// `${_workflow}`, and `${_tags}` (but not from other fields).
+    #[serde(default)]
+    pub fields: HashMap<String, Template>,
+    /// The properties of a button for copying a link to the step the user is on, which will take whoever opens it to the same place with the
+    /// same tags (and, unless disabled, the same answers). If this isn't provided, there won't be a button.
+    #[serde(default)]
+    pub share: Option<ShareLink>,
+}
+/// The properties of a button for copying a link to the step the user is on (e.g. so a maintainer can send a contributor to exactly where
+/// they need to be). Values from password inputs and inputs marked as `sensitive` are never put in these links.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShareLink {
+    /// The text of the button. This does not support Markdown.
+    #[serde(default = "default_share_text")]
+    pub text: String,
+    /// The text the button changes to once the link has been copied. This does not support Markdown.
+    #[serde(default = "default_share_copied_text")]
+    pub copied_text: String,
+    /// The text shown above the link if it couldn't be copied to the user's clipboard (e.g. because the site isn't served over HTTPS). This
+    /// does not support Markdown.
+    #[serde(default = "default_share_copy_failed_text")]
+    pub copy_failed_text: String,
+    /// Whether or not the values of inputs should be put in the link, as well as where the user is and what tags they've accumulated.
+    #[serde(default = "default_true")]
+    pub include_values: bool,
+}
+/// The parts of a workflow that can be filled in from the query parameters of a link to it (e.g.
+/// `/workflow/test?version=1.4.2&start=Report%20Bug`), which is useful for linking to a workflow from error messages.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Prefill {
+    /// The IDs of the inputs that can be filled in (e.g. `version` for `?version=1.4.2`). Inputs that allow multiple options can be given
+    /// several values by repeating the parameter, and any values that aren't options of the input will be ignored.
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    /// The sections that the workflow can be started at instead of its index (with `?start=<section>`). The user won't have accumulated any
+    /// tags from the sections they skipped.
+    #[serde(default)]
+    pub sections: Vec<String>,
 }
 /// A type alias for a section, which is simply an ordered list of elements.
 pub type Section = Vec<SectionElem>;
@@ -78,9 +331,48 @@ pub enum SectionElem {
         link: String,
         /// Any tags that should be accumulated as a result of proceeding through this route.
         tags: Vec<String>,
+        /// A condition that must be true for this progression to be shown (e.g. `tags contains "A:frontend"`).
+        #[serde(default)]
+        when: Option<Condition>,
+    },
+    /// An automatic progression to another section or endpoint, which is followed as soon as the user would arrive at this section (without them
+    /// having to do anything) if its condition is true. If there are multiple redirects in a section, the first one whose condition is true will
+    /// be followed. Sections that redirect the user elsewhere won't appear in their history, so going back will skip over them.
+    Redirect {
+        /// The name of the section to navigate to. If this is prefixed with `endpoint:`, it will navigate to an endpoint instead of a section.
+        redirect: String,
+        /// A condition that must be true for this redirect to be followed. If this isn't provided, the redirect will always be followed.
+        #[serde(default)]
+        when: Option<Condition>,
+        /// Any tags that should be accumulated as a result of being redirected. These will count as being accumulated in the section the user
+        /// came from (redirects in the index section can't accumulate tags, because there's nowhere for them to go).
+        #[serde(default)]
+        tags: Vec<String>,
     },
     /// A form input that the user can fill out. This must have an associated ID, because its value can be referenced later in an endpoint.
     Input(InputSectionElem),
+    /// A group of inputs that the user can fill out as many times as they need to (e.g. once for each step to reproduce a bug).
+    Group(InputGroup),
+    /// Text that will only be displayed to the user if the given condition is true. Markdown is supported here, just like in simple text.
+    ConditionalText {
+        /// The text to display.
+        text: String,
+        /// A condition that must be true for this text to be shown (e.g. `${severity} == "high"`).
+        when: Condition,
+    },
+}
+impl SectionElem {
+    /// Gets the condition that must be true for this element to be shown (or, for a redirect, to be followed), if there is one.
+    pub fn when(&self) -> Option<&Condition> {
+        match self {
+            Self::Text(_) => None,
+            Self::Progression { when, .. } | Self::Redirect { when, .. } => when.as_ref(),
+            Self::Input(InputSectionElem { when, .. }) | Self::Group(InputGroup { when, .. }) => {
+                when.as_ref()
+            }
+            Self::ConditionalText { when, .. } => Some(when),
+        }
+    }
 }
 /// The properties of an input element. This needs to be passed around, so it's broken out of the `SectionElem` input.
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
@@ -90,20 +382,96 @@ pub struct InputSectionElem {
     pub id: String,
     /// The label for the input. This does not support Markdown.
     pub label: String,
+    /// A longer description of what the user should enter, shown underneath the input. Markdown is supported here.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// A short tip for filling out the input (e.g. where to find the information it asks for), shown underneath it. This does not support
+    /// Markdown.
+    #[serde(default)]
+    pub hint: Option<String>,
+    /// An example of what the user might enter (e.g. `For example: 1.2.3`), shown underneath the input. This does not support Markdown.
+    #[serde(default)]
+    pub example: Option<String>,
     /// Whether or not the input is optional.
     #[serde(default)]
     pub optional: bool,
+    /// A condition that must be true for this input to be shown. Hidden inputs are never required, and they won't accumulate any tags.
+    #[serde(default)]
+    pub when: Option<Condition>,
+    /// Whether or not this input's value should be left out of links to a step and progress saved in the browser (password inputs always
+    /// are).
+    #[serde(default)]
+    pub sensitive: bool,
     /// The default value for the input. If the input is optional, this will be the value used for interpolation. If the input is not optional, this will be the default,
     /// which means it will be left as this if the user doesn't fill it in. If a value should be provided, you should make it mandatory and set a default, as optional fields should
     /// be assumed to potentially not contain any value (even though they always will if a default value is provided).
     ///
-    /// If the input is a `Select`, this must correspond to an entry in `options`.
-    pub default: Option<String>,
+    /// If the input has options, this must correspond to one of them, or, if the user can select multiple options, it can be a list of them.
+    /// This must follow the input's own validation rules.
+    pub default: Option<DefaultValue>,
+    /// Rules that the value of this input must follow before the user can move on from its section, each with its own error message.
+    #[serde(default)]
+    pub validate: Vec<Validation>,
+    /// Rules that add tags depending on the value the user enters (e.g. `{ pattern: "0\\..*", tags: [ "V:pre-1.0" ] }`), which are
+    /// accumulated alongside the tags of options.
+    #[serde(default)]
+    pub tag_rules: Vec<TagRule>,
     /// The actual properties of the input (unique depending on the input's type).
     #[serde(flatten)]
     // The user can just continue to supply these properties without having to put them inside `input`
     pub input: Input,
 }
+impl InputSectionElem {
+    /// Checks if this input's value should be kept out of links to a step and saved progress, which it should be if it's a password or it's
+    /// been marked as sensitive.
+    pub fn is_sensitive(&self) -> bool {
+        self.sensitive
+            || matches!(
+                self.input,
+                Input::Text {
+                    input_type: InputType::Password
+                }
+            )
+    }
+}
+/// The properties of a group of inputs that can be repeated, with buttons for the user to add and remove entries.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InputGroup {
+    /// The group's ID, which can be used to reference its entries later in a formatted report (e.g. `{% for step in ${steps} %}`). This
+    /// must be unique among the IDs of inputs as well as groups.
+    pub group: String,
+    /// The label for the group as a whole. This does not support Markdown.
+    pub label: String,
+    /// The inputs in each entry. Their IDs only have to be unique within the group, and their values are referenced through an entry
+    /// (e.g. `${step.description}` inside a loop over the group).
+    pub inputs: Vec<InputSectionElem>,
+    /// The fewest entries the user can have, which is also how many will be shown to start with.
+    #[serde(default = "default_group_min")]
+    pub min: usize,
+    /// The most entries the user can have. If this isn't provided, they can add as many as they like.
+    #[serde(default)]
+    pub max: Option<usize>,
+    /// The text of the button for adding another entry. This does not support Markdown.
+    #[serde(default = "default_add_text")]
+    pub add_text: String,
+    /// The text of the button for removing an entry. This does not support Markdown.
+    #[serde(default = "default_remove_text")]
+    pub remove_text: String,
+    /// A condition that must be true for this group to be shown. Hidden groups are never required, and they won't accumulate any tags.
+    #[serde(default)]
+    pub when: Option<Condition>,
+}
+/// The default value of an input.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum DefaultValue {
+    /// A single value (for a boolean input, this should be `"true"` or `"false"`).
+    Single(String),
+    /// A list of options, for an input that lets the user select multiple options.
+    List(Vec<String>),
+}
 /// The different types of inputs.
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -119,15 +487,64 @@ pub enum Input {
     /// A select element that provides a dropdown for the user to select a single option.
     Select {
         /// The options that the user can select from.
-        options: Vec<SelectOption>,
+        options: OptionSource,
         /// Whether or not the user can select multiple options.
         #[serde(default)]
         can_select_multiple: bool,
     },
+    /// A group of radio buttons that lets the user select a single option. For a handful of options, this is usually nicer than a `Select`,
+    /// because all the options are visible at once.
+    RadioGroup {
+        /// The options that the user can select from.
+        radio: OptionSource,
+    },
+    /// A group of checkboxes that lets the user select any number of options.
+    CheckboxGroup {
+        /// The options that the user can select from.
+        checkboxes: OptionSource,
+    },
+}
+impl Input {
+    /// Gets the options the user can select from, if this is an input that has them.
+    pub fn options(&self) -> Option<&[SelectOption]> {
+        self.option_source().map(|source| source.options())
+    }
+    /// Gets where the options the user can select from are defined, if this is an input that has them.
+    pub fn option_source(&self) -> Option<&OptionSource> {
+        match self {
+            Self::Text { .. } => None,
+            Self::Select { options, .. }
+            | Self::RadioGroup { radio: options }
+            | Self::CheckboxGroup {
+                checkboxes: options,
+            } => Some(options),
+        }
+    }
+    /// Gets where the options the user can select from are defined (mutably), if this is an input that has them.
+    pub fn option_source_mut(&mut self) -> Option<&mut OptionSource> {
+        match self {
+            Self::Text { .. } => None,
+            Self::Select { options, .. }
+            | Self::RadioGroup { radio: options }
+            | Self::CheckboxGroup {
+                checkboxes: options,
+            } => Some(options),
+        }
+    }
+    /// Checks if this input lets the user select multiple options.
+    pub fn is_multiple(&self) -> bool {
+        matches!(
+            self,
+            Self::Select {
+                can_select_multiple: true,
+                ..
+            } | Self::CheckboxGroup { .. }
+        )
+    }
 }
 /// The possible types an input can have.
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(tag = "type")]
 #[serde(rename_all = "kebab-case")]
 pub enum InputType {
@@ -138,9 +555,23 @@ pub enum InputType {
     },
     /// A multiline text input.
     Multiline,
+    /// A multiline input for code or logs, which is shown in a monospace font and interpolated into reports as a fenced code block.
+    Code {
+        /// The language the code should be highlighted as in the report (e.g. `rust` or `console`).
+        #[serde(default)]
+        language: Option<CodeLanguage>,
+        /// The maximum number of lines of the code to show in the report. Anything longer than this will be cut short, and the full code
+        /// will be put in a collapsible section underneath.
+        #[serde(default)]
+        max_lines: Option<usize>,
+        /// The text of the toggle for the collapsible section containing the full code, if it had to be cut short.
+        #[serde(default = "default_truncated_msg")]
+        truncated_msg: String,
+    },
     /// A color picker (only in supported browsers).
     Color,
     /// A simple text element (default).
+    #[default]
     Text,
     /// A date input.
     Date,
@@ -177,33 +608,105 @@ pub enum InputType {
     /// A week input.
     Week,
 }
-impl Default for InputType {
-    fn default() -> Self {
-        Self::Text
+impl fmt::Display for InputType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Boolean { .. } => "checkbox",
+            Self::Multiline => "multiline",
+            Self::Code { .. } => "code",
+            Self::Color => "color",
+            Self::Text => "text",
+            Self::Date => "date",
+            Self::DatetimeLocal => "datetime-local",
+            Self::Email => "email",
+            Self::Month => "month",
+            Self::Number { .. } => "number",
+            Self::Password => "password",
+            Self::Range { .. } => "range",
+            Self::Tel => "tel",
+            Self::Time => "time",
+            Self::Url => "url",
+            Self::Week => "week",
+        };
+        write!(f, "{}", name)
+    }
+}
+/// The language of a code input, which goes straight after the opening fence of the code block in a report. This is checked when the
+/// configuration is parsed, because whitespace or backticks would break out of the info string.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct CodeLanguage(String);
+impl CodeLanguage {
+    /// Gets the language as text.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+impl TryFrom<String> for CodeLanguage {
+    type Error = String;
+
+    fn try_from(language: String) -> Result<Self, Self::Error> {
+        if language.is_empty() || language.contains(|c: char| c.is_whitespace() || c == '`') {
+            Err(format!(
+                "invalid code language '{}' (it must be a single word, like `rust` or `console`, without any backticks)",
+                language
+            ))
+        } else {
+            Ok(Self(language))
+        }
+    }
+}
+impl From<CodeLanguage> for String {
+    fn from(language: CodeLanguage) -> Self {
+        language.0
     }
 }
-impl ToString for InputType {
-    fn to_string(&self) -> String {
+#[cfg(feature = "schema")]
+impl JsonSchema for CodeLanguage {
+    fn schema_name() -> String {
+        "CodeLanguage".to_string()
+    }
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+/// Where the options of a select element, radio group, or checkbox group come from.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum OptionSource {
+    /// The options themselves.
+    Inline(Vec<SelectOption>),
+    /// A data file to load the options from when the app is built, which is useful for long lists that change often (and that would
+    /// otherwise have to be copied into every language file).
+    File(OptionsFile),
+}
+impl OptionSource {
+    /// Gets the options. Data files are loaded when the app is built (see [`crate::option_files`]), so, until then, these won't have any
+    /// options.
+    pub fn options(&self) -> &[SelectOption] {
         match self {
-            Self::Boolean { .. } => "checkbox".to_string(),
-            Self::Multiline => "multiline".to_string(),
-            Self::Color => "color".to_string(),
-            Self::Text => "text".to_string(),
-            Self::Date => "date".to_string(),
-            Self::DatetimeLocal => "datetime-local".to_string(),
-            Self::Email => "email".to_string(),
-            Self::Month => "month".to_string(),
-            Self::Number { .. } => "number".to_string(),
-            Self::Password => "password".to_string(),
-            Self::Range { .. } => "range".to_string(),
-            Self::Tel => "tel".to_string(),
-            Self::Time => "time".to_string(),
-            Self::Url => "url".to_string(),
-            Self::Week => "week".to_string(),
+            Self::Inline(options) => options,
+            Self::File(_) => &[],
         }
     }
 }
-/// The properties for an option for a select element. The text of this MUST NOT contain commas, otherwise all sorts of runtime errors WILL occur!
+/// A reference to a YAML, JSON, or CSV file to load the options of an input from. In a YAML or JSON file, this should be a list of options,
+/// each of which is either a string or a map with fields for its text and tags. In a CSV file, each row is an option, and the tags are
+/// separated by spaces.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OptionsFile {
+    /// The path to the file, relative to the configuration file that references it. The file type is determined by its extension.
+    pub file: String,
+    /// The field (or column in a CSV file) that holds the text of each option.
+    #[serde(default = "default_text_field")]
+    pub text: String,
+    /// The field (or column in a CSV file) that holds the tags of each option. Options don't have to have any tags.
+    #[serde(default = "default_tags_field")]
+    pub tags: String,
+}
+/// The properties for an option for a select element, radio group, or checkbox group.
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
@@ -217,25 +720,209 @@ pub enum SelectOption {
         tags: Vec<String>,
     },
 }
+impl SelectOption {
+    /// Gets the text of this option, which is also its value.
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Simple(text) | Self::WithTags { text, .. } => text,
+        }
+    }
+    /// Gets the tags that will be accumulated if this option is selected.
+    pub fn tags(&self) -> &[String] {
+        match self {
+            Self::Simple(_) => &[],
+            Self::WithTags { tags, .. } => tags,
+        }
+    }
+}
 /// The possible endpoint types (endpoints are sections that allow the user to exit the contribution process).
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
+// There are only ever a handful of these, so boxing the report variant wouldn't save anything worth the extra indirection
+#[allow(clippy::large_enum_variant)]
 pub enum Endpoint {
     /// A report endpoint, which gives the user a formatted report in Markdown to send to the project.
-    // TODO Add functionality to actually send the report somewhere
     Report {
-        /// The preamble text to display before the actual formatted report. Markdown can be used here.
-        preamble: String,
-        /// The formatted report. The UI will not allow the user to edit this, but will provide a copy button. Interpolation of form values is allowed here with `${form_id}` syntax. This
-        /// should be written in the appropriate templating language for your issue reporting system (e.g. Markdown for GitHub issues), and will be displayed as a raw, pre-formatted string.
-        text: String,
+        /// The preamble text to display before the actual formatted report. Markdown and templating (see `text`) can be used here, though
+        /// anything filled in from inputs is shown as plain text.
+        preamble: Template,
+        /// The formatted report. The UI will not allow the user to edit this, but will provide a copy button. This is a template, so form values
+        /// can be interpolated with `${form_id}` syntax, and filters, conditionals, and loops can be used too (see [`Template`]). This should be
+        /// written in the appropriate templating language for your issue reporting system (e.g. Markdown for GitHub issues), and will be
+        /// displayed as a raw, pre-formatted string.
+        text: Template,
         /// The text of a button for sending teh user to wherever they'll report the issue. This does not support Markdown.
         dest_text: String,
         /// A URL to send the user to so that they can report the issue. If the platform supports interpolating text to be sent
-        /// into the URL, you can do so by interpolating `%s` into this field.
-        dest_url: String,
+        /// into the URL, you can do so by interpolating `%s` into this field. If a structured destination (`github`, `gitlab`, or `gitea`) is
+        /// provided, this will be ignored, and if neither are provided, the user will only be able to copy the report. This is a template too
+        /// (see `text`), and any form values interpolated into it will be URL-encoded.
+        #[serde(default)]
+        dest_url: Option<Template>,
+        /// The maximum length of the URL generated from `dest_url`. If the report would make the URL longer than this, it will instead be
+        /// copied to the user's clipboard when they click the button, and `copied_msg` will be sent in its place.
+        #[serde(default = "default_max_url_len")]
+        max_url_len: usize,
+        /// The message to display (and send in place of the report) if the report is too long to be sent in a URL. If this would make the
+        /// URL too long as well, nothing will be sent in the report's place.
+        #[serde(default = "default_copied_msg")]
+        copied_msg: String,
+        /// The message to display if the report couldn't be copied to the user's clipboard (e.g. because the site isn't served over HTTPS, or
+        /// because the user didn't give permission). The text that should have been copied is shown underneath it, so the user can copy it
+        /// themselves.
+        #[serde(default = "default_copy_failed_msg")]
+        copy_failed_msg: String,
+        /// A GitHub repository to send the user to for creating a new issue, which supports setting the title, labels, and assignees
+        /// natively (without needing the Tribble bot).
+        #[serde(default)]
+        github: Option<GithubDest>,
+        /// A GitLab project to send the user to for creating a new issue. Labels, assignees, and milestones are set with quick actions at the
+        /// end of the issue description.
+        #[serde(default)]
+        gitlab: Option<GitlabDest>,
+        /// A Gitea or Forgejo repository to send the user to for creating a new issue.
+        #[serde(default)]
+        gitea: Option<GiteaDest>,
+        /// Settings for submitting the report directly to an HTTP endpoint (e.g. your own issue-tracking service), which will give the user a
+        /// button to do so.
+        #[serde(default)]
+        submit: Option<ReportSubmit>,
     },
     /// An instructional endpoint, which tells the user to do something. This supports Markdown.
     Instructional(String),
 }
+/// Gets the default GitHub instance to send reports to.
+fn default_github_host() -> String {
+    "https://github.com".to_string()
+}
+/// The properties for sending a report to GitHub as a new issue. The report text will be the body of the issue, and accumulated tags will be
+/// mapped onto labels. Note that GitHub will only apply labels, assignees, and milestones if the user has permission to set them.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GithubDest {
+    /// The repository to create the issue in (e.g. `arctic-hen7/tribble`).
+    pub repo: String,
+    /// The title of the issue. This is a template, so form values can be interpolated with `${form_id}` syntax.
+    #[serde(default)]
+    pub title: Option<Template>,
+    /// Labels that should always be added to the issue.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// A map of tags to the labels that should be added to the issue if they've been accumulated. Tags that aren't in here won't add any labels.
+    #[serde(default)]
+    pub tag_labels: HashMap<String, String>,
+    /// The usernames of the people the issue should be assigned to.
+    #[serde(default)]
+    pub assignees: Vec<String>,
+    /// The name of the milestone the issue should be added to.
+    #[serde(default)]
+    pub milestone: Option<String>,
+    /// The filename of an issue template in `.github/ISSUE_TEMPLATE/` to use.
+    #[serde(default)]
+    pub template: Option<String>,
+    /// The base URL of the GitHub instance, which only needs to be set for GitHub Enterprise.
+    #[serde(default = "default_github_host")]
+    pub host: String,
+    /// The maximum length of the generated URL, above which the report will be copied to the user's clipboard instead of being sent in the URL.
+    #[serde(default = "default_max_url_len")]
+    pub max_url_len: usize,
+}
+/// Gets the default GitLab instance to send reports to.
+fn default_gitlab_host() -> String {
+    "https://gitlab.com".to_string()
+}
+/// The properties for sending a report to GitLab as a new issue. The report text will be the description of the issue, and accumulated tags will
+/// be mapped onto labels. Labels, assignees, and milestones are set with quick actions, which GitLab will only apply if the user has permission
+/// to set them.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GitlabDest {
+    /// The full path of the project to create the issue in (e.g. `my-group/my-subgroup/my-project`).
+    pub project: String,
+    /// The title of the issue. This is a template, so form values can be interpolated with `${form_id}` syntax.
+    #[serde(default)]
+    pub title: Option<Template>,
+    /// Labels that should always be added to the issue.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// A map of tags to the labels that should be added to the issue if they've been accumulated. Tags that aren't in here won't add any labels.
+    #[serde(default)]
+    pub tag_labels: HashMap<String, String>,
+    /// The usernames of the people the issue should be assigned to.
+    #[serde(default)]
+    pub assignees: Vec<String>,
+    /// The title of the milestone the issue should be added to.
+    #[serde(default)]
+    pub milestone: Option<String>,
+    /// The name of a description template in `.gitlab/issue_templates/` to use.
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Whether or not the issue should be marked as confidential.
+    #[serde(default)]
+    pub confidential: bool,
+    /// The base URL of the GitLab instance, which only needs to be set for self-hosted instances.
+    #[serde(default = "default_gitlab_host")]
+    pub host: String,
+    /// The maximum length of the generated URL, above which the report will be copied to the user's clipboard instead of being sent in the URL.
+    #[serde(default = "default_max_url_len")]
+    pub max_url_len: usize,
+}
+/// The properties for sending a report to Gitea or Forgejo (e.g. Codeberg) as a new issue. The report text will be the body of the issue, and
+/// accumulated tags will be mapped onto labels.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GiteaDest {
+    /// The base URL of the instance (e.g. `https://codeberg.org`).
+    pub host: String,
+    /// The repository to create the issue in (e.g. `my-org/my-repo`).
+    pub repo: String,
+    /// The title of the issue. This is a template, so form values can be interpolated with `${form_id}` syntax.
+    #[serde(default)]
+    pub title: Option<Template>,
+    /// The numerical IDs of labels that should always be added to the issue (Gitea and Forgejo don't support adding labels by name through the URL).
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// A map of tags to the numerical IDs of the labels that should be added to the issue if they've been accumulated.
+    #[serde(default)]
+    pub tag_labels: HashMap<String, String>,
+    /// The maximum length of the generated URL, above which the report will be copied to the user's clipboard instead of being sent in the URL.
+    #[serde(default = "default_max_url_len")]
+    pub max_url_len: usize,
+}
+/// The properties for submitting a report directly to an HTTP endpoint.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReportSubmit {
+    /// The URL to send a `POST` request to. The body will be a JSON object containing the names of the `workflow` and `endpoint`, the interpolated
+    /// report `text`, the accumulated `tags`, and the raw `form_values` the user entered. Any `2xx` response is considered a success.
+    pub url: String,
+    /// The text of the button that submits the report. This does not support Markdown.
+    pub text: String,
+    /// Any extra headers to send with the request (e.g. a public API key). Remember that these will be visible to anyone who uses your Tribble instance!
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// The message to display once the report has been submitted. Markdown can be used here.
+    #[serde(default = "default_submit_success_msg")]
+    pub success_msg: String,
+    /// The message to display if the report couldn't be submitted (the user will be able to try again). This does not support Markdown.
+    #[serde(default = "default_submit_err_msg")]
+    pub err_msg: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_languages_are_checked() {
+        let input_type = |yaml: &str| serde_yaml::from_str::<InputType>(yaml);
+        assert!(matches!(
+            input_type("type: code\nlanguage: rust").unwrap(),
+            InputType::Code { language: Some(language), .. } if language.as_str() == "rust"
+        ));
+        assert!(input_type("type: code\nlanguage: \"rust foo\"").is_err());
+        assert!(input_type("type: code\nlanguage: \"rust`\"").is_err());
+        assert!(input_type("type: code\nlanguage: \"\"").is_err());
+    }
+}
@@ -0,0 +1,402 @@
+use crate::form::FormValue;
+use crate::parser::{GiteaDest, GithubDest, GitlabDest};
+use std::collections::HashMap;
+
+/// A link to the place where a report should be sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DestLink {
+    /// The report fits entirely in the URL.
+    Full(String),
+    /// The report was too long for the URL, so it's been replaced with a placeholder message (or left out entirely, if even that was too
+    /// long), and the given body should be copied to the user's clipboard instead.
+    Shortened { url: String, body: String },
+}
+
+/// Builds a link to a report destination with the given URL builder, falling back to a URL with the given placeholder in place of the
+/// body if the full URL would be longer than the maximum length. If the placeholder is too long as well, the body is left empty.
+pub fn fit_url(
+    build: impl Fn(&str) -> String,
+    body: &str,
+    placeholder: &str,
+    max_len: usize,
+) -> DestLink {
+    let url = build(body);
+    if url.len() <= max_len {
+        DestLink::Full(url)
+    } else {
+        let url = build(placeholder);
+        let url = if url.len() <= max_len { url } else { build("") };
+        DestLink::Shortened {
+            url,
+            body: body.to_string(),
+        }
+    }
+}
+
+/// Builds a URL for creating a new GitHub issue with the given report body, using GitHub's native query parameters for everything else.
+/// Accumulated tags are mapped onto labels using the destination's tag-to-label table.
+pub fn github_url(
+    dest: &GithubDest,
+    values: &HashMap<String, FormValue>,
+    body: &str,
+    tags: &[String],
+) -> String {
+    let mut params = Vec::new();
+    if let Some(title) = &dest.title {
+        params.push(("title", title.render(values, tags)));
+    }
+    params.push(("body", body.to_string()));
+    let labels = collect_labels(&dest.labels, &dest.tag_labels, tags);
+    if !labels.is_empty() {
+        params.push(("labels", labels.join(",")));
+    }
+    if !dest.assignees.is_empty() {
+        params.push(("assignees", dest.assignees.join(",")));
+    }
+    if let Some(milestone) = &dest.milestone {
+        params.push(("milestone", milestone.to_string()));
+    }
+    if let Some(template) = &dest.template {
+        params.push(("template", template.to_string()));
+    }
+
+    format!(
+        "{}/{}/issues/new?{}",
+        dest.host.trim_end_matches('/'),
+        dest.repo,
+        encode_query(&params)
+    )
+}
+
+/// Builds a URL for creating a new GitLab issue with the given report body. GitLab doesn't support setting labels, assignees, or milestones
+/// through the URL, so we add quick actions to the end of the description for those instead.
+pub fn gitlab_url(
+    dest: &GitlabDest,
+    values: &HashMap<String, FormValue>,
+    body: &str,
+    tags: &[String],
+) -> String {
+    let mut description = body.to_string();
+    let mut quick_actions = Vec::new();
+    let labels = collect_labels(&dest.labels, &dest.tag_labels, tags);
+    if !labels.is_empty() {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|label| format!("~\"{}\"", strip_quotes(label)))
+            .collect();
+        quick_actions.push(format!("/label {}", labels.join(" ")));
+    }
+    if !dest.assignees.is_empty() {
+        let assignees: Vec<String> = dest
+            .assignees
+            .iter()
+            .map(|user| format!("@{}", user.trim_start_matches('@')))
+            .collect();
+        quick_actions.push(format!("/assign {}", assignees.join(" ")));
+    }
+    if let Some(milestone) = &dest.milestone {
+        quick_actions.push(format!("/milestone %\"{}\"", strip_quotes(milestone)));
+    }
+    if dest.confidential {
+        quick_actions.push("/confidential".to_string());
+    }
+    // Quick actions have to be on their own lines
+    if !quick_actions.is_empty() {
+        description.push_str("\n\n");
+        description.push_str(&quick_actions.join("\n"));
+    }
+
+    let mut params = Vec::new();
+    if let Some(title) = &dest.title {
+        params.push(("issue[title]", title.render(values, tags)));
+    }
+    params.push(("issue[description]", description));
+    if let Some(template) = &dest.template {
+        params.push(("issuable_template", template.to_string()));
+    }
+
+    format!(
+        "{}/{}/-/issues/new?{}",
+        dest.host.trim_end_matches('/'),
+        dest.project.trim_matches('/'),
+        encode_query(&params)
+    )
+}
+
+/// Builds a URL for creating a new Gitea or Forgejo issue with the given report body. These only support setting labels by their numerical
+/// IDs, so that's what the destination's labels should be.
+pub fn gitea_url(
+    dest: &GiteaDest,
+    values: &HashMap<String, FormValue>,
+    body: &str,
+    tags: &[String],
+) -> String {
+    let mut params = Vec::new();
+    if let Some(title) = &dest.title {
+        params.push(("title", title.render(values, tags)));
+    }
+    params.push(("body", body.to_string()));
+    let labels = collect_labels(&dest.labels, &dest.tag_labels, tags);
+    if !labels.is_empty() {
+        params.push(("labels", labels.join(",")));
+    }
+
+    format!(
+        "{}/{}/issues/new?{}",
+        dest.host.trim_end_matches('/'),
+        dest.repo,
+        encode_query(&params)
+    )
+}
+
+/// Collects the labels that should be added to an issue from a static list and the labels mapped from accumulated tags, making sure each one
+/// is only added once.
+fn collect_labels<'a>(
+    labels: &'a [String],
+    tag_labels: &'a HashMap<String, String>,
+    tags: &[String],
+) -> Vec<&'a str> {
+    let mut collected: Vec<&str> = Vec::new();
+    let from_tags = tags.iter().filter_map(|tag| tag_labels.get(tag));
+    for label in labels.iter().chain(from_tags) {
+        if !collected.contains(&label.as_str()) {
+            collected.push(label);
+        }
+    }
+
+    collected
+}
+
+/// Removes double quotes from a name that will be quoted in a GitLab quick action (there's no way to escape them).
+fn strip_quotes(name: &str) -> String {
+    name.replace('"', "")
+}
+
+/// Encodes the given parameters into a query string.
+fn encode_query(params: &[(&str, String)]) -> String {
+    params
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "{}={}",
+                urlencoding::encode(key),
+                urlencoding::encode(value)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dest<T: serde::de::DeserializeOwned>(yaml: &str) -> T {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+    fn values(values: &[(&str, &str)]) -> HashMap<String, FormValue> {
+        values
+            .iter()
+            .map(|(id, value)| (id.to_string(), FormValue::Single(value.to_string())))
+            .collect()
+    }
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+    /// Splits a URL into its base and its decoded query parameters.
+    fn parse_url(url: &str) -> (&str, Vec<(String, String)>) {
+        let (base, query) = url.split_once('?').unwrap();
+        let params = query
+            .split('&')
+            .map(|param| {
+                let (key, value) = param.split_once('=').unwrap();
+                (
+                    urlencoding::decode(key).unwrap().into_owned(),
+                    urlencoding::decode(value).unwrap().into_owned(),
+                )
+            })
+            .collect();
+        (base, params)
+    }
+
+    #[test]
+    fn encodes_reserved_and_non_ascii_characters() {
+        let params = [("a b", "x&y=z".to_string()), ("c", "#1 café?".to_string())];
+        assert_eq!(
+            encode_query(&params),
+            "a%20b=x%26y%3Dz&c=%231%20caf%C3%A9%3F"
+        );
+    }
+
+    #[test]
+    fn collects_labels_once() {
+        let labels = tags(&["bug", "triage"]);
+        let tag_labels: HashMap<String, String> = [("crash", "bug"), ("ui", "area: ui")]
+            .iter()
+            .map(|(tag, label)| (tag.to_string(), label.to_string()))
+            .collect();
+        assert_eq!(
+            collect_labels(
+                &labels,
+                &tag_labels,
+                &tags(&["ui", "crash", "ui", "unmapped"])
+            ),
+            vec!["bug", "triage", "area: ui"]
+        );
+    }
+
+    #[test]
+    fn builds_github_urls() {
+        let dest: GithubDest = dest(
+            r#"
+repo: "user/repo"
+title: "[Bug] ${summary}"
+labels: ["bug", "good first issue"]
+tag_labels:
+  crash: "bug"
+  perf: "área: perf"
+assignees: ["alice", "bob"]
+milestone: "v1.0 & beyond"
+template: "bug.md"
+"#,
+        );
+        let url = github_url(
+            &dest,
+            &values(&[("summary", "Crash on #1 & more")]),
+            "Steps: a b\n\nÇa plante",
+            &tags(&["crash", "perf"]),
+        );
+        assert_eq!(
+            url,
+            "https://github.com/user/repo/issues/new?\
+            title=%5BBug%5D%20Crash%20on%20%231%20%26%20more\
+            &body=Steps%3A%20a%20b%0A%0A%C3%87a%20plante\
+            &labels=bug%2Cgood%20first%20issue%2C%C3%A1rea%3A%20perf\
+            &assignees=alice%2Cbob\
+            &milestone=v1.0%20%26%20beyond\
+            &template=bug.md"
+        );
+    }
+
+    #[test]
+    fn github_urls_leave_out_unused_params() {
+        let dest: GithubDest = dest("repo: user/repo\nhost: https://github.example.com/\n");
+        assert_eq!(
+            github_url(&dest, &HashMap::new(), "Body", &tags(&["crash"])),
+            "https://github.example.com/user/repo/issues/new?body=Body"
+        );
+    }
+
+    #[test]
+    fn builds_gitlab_urls_with_quick_actions() {
+        let dest: GitlabDest = dest(
+            r#"
+project: "/group/subgroup/project/"
+title: "Bug: ${summary}"
+labels: ["bug", "needs \"triage\""]
+tag_labels:
+  ui: "area::user interface"
+  crash: "bug"
+assignees: ["@alice", "bob"]
+milestone: "v1.0 \"final\""
+template: "Bug Report"
+confidential: true
+"#,
+        );
+        let url = gitlab_url(
+            &dest,
+            &values(&[("summary", "50% & #1")]),
+            "Body",
+            &tags(&["ui", "crash"]),
+        );
+        let (base, params) = parse_url(&url);
+        assert_eq!(
+            base,
+            "https://gitlab.com/group/subgroup/project/-/issues/new"
+        );
+        assert_eq!(
+            params,
+            vec![
+                ("issue[title]".to_string(), "Bug: 50% & #1".to_string()),
+                (
+                    "issue[description]".to_string(),
+                    "Body\n\n\
+                    /label ~\"bug\" ~\"needs triage\" ~\"area::user interface\"\n\
+                    /assign @alice @bob\n\
+                    /milestone %\"v1.0 final\"\n\
+                    /confidential"
+                        .to_string()
+                ),
+                ("issuable_template".to_string(), "Bug Report".to_string()),
+            ]
+        );
+        // Everything in the query is escaped
+        assert!(url.contains("issue%5Btitle%5D=Bug%3A%2050%25%20%26%20%231"));
+    }
+
+    #[test]
+    fn gitlab_urls_without_quick_actions_leave_body_alone() {
+        let dest: GitlabDest = dest("project: group/project\n");
+        assert_eq!(
+            gitlab_url(&dest, &HashMap::new(), "Body", &[]),
+            "https://gitlab.com/group/project/-/issues/new?issue%5Bdescription%5D=Body"
+        );
+    }
+
+    #[test]
+    fn builds_gitea_urls() {
+        let dest: GiteaDest = dest(
+            r#"
+host: "https://codeberg.org/"
+repo: "user/repo"
+title: "${summary}"
+labels: ["1", "2"]
+tag_labels:
+  crash: "2"
+  "needs triage": "5"
+"#,
+        );
+        let url = gitea_url(
+            &dest,
+            &values(&[("summary", "Doesn't \"work\" & crashes")]),
+            "Body with spaces",
+            &tags(&["crash", "needs triage"]),
+        );
+        assert_eq!(
+            url,
+            "https://codeberg.org/user/repo/issues/new?\
+            title=Doesn%27t%20%22work%22%20%26%20crashes\
+            &body=Body%20with%20spaces\
+            &labels=1%2C2%2C5"
+        );
+        let (_, params) = parse_url(&url);
+        assert_eq!(params[0].1, "Doesn't \"work\" & crashes");
+    }
+
+    #[test]
+    fn fits_reports_in_urls() {
+        let build =
+            |body: &str| format!("https://example.com/new?body={}", urlencoding::encode(body));
+        // `https://example.com/new?body=` is 29 characters
+        assert_eq!(
+            fit_url(build, "short", "Paste it!", 34),
+            DestLink::Full("https://example.com/new?body=short".to_string())
+        );
+        // Encoding counts towards the length
+        assert_eq!(
+            fit_url(build, "a b c", "Paste", 37),
+            DestLink::Shortened {
+                url: "https://example.com/new?body=Paste".to_string(),
+                body: "a b c".to_string(),
+            }
+        );
+        // If the placeholder doesn't fit either, the body is left out of the URL
+        assert_eq!(
+            fit_url(build, "a long report", "A very long placeholder", 40),
+            DestLink::Shortened {
+                url: "https://example.com/new?body=".to_string(),
+                body: "a long report".to_string(),
+            }
+        );
+    }
+}
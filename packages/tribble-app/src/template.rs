@@ -0,0 +1,1004 @@
+use crate::condition::Condition;
+use crate::errors::TemplateError;
+use crate::form::FormValue;
+#[cfg(feature = "schema")]
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// A template that's filled in with the values the user has entered into inputs and the tags they've accumulated, used for reports. For
+/// example, `${description | quote}`.
+///
+/// The language supports:
+///
+/// - `${id}` -- the value of the input with the given ID (this is empty if it hasn't been filled out)
+/// - `${id | filter}` -- the value passed through one or more filters, which are applied from left to right:
+///   - `upper` -- converts the value to uppercase
+///   - `code` -- wraps the value in an inline code span, or a fenced code block if it spans multiple lines
+///   - `quote` -- turns the value into a Markdown blockquote
+///   - `list` -- turns the options selected in an input that allows multiple options into a Markdown list
+///   - `default("<text>")` -- uses the given text if the value is empty
+///
+///   Values from code inputs are filled in as fenced code blocks (cut short if they're longer than the input allows), unless they're passed
+///   through a filter that changes them (like `upper` or `quote`).
+/// - `{% if <condition> %}...{% else %}...{% end %}` -- only includes its contents if the given condition is true (see [`Condition`] for
+///   the syntax), the `else` is optional
+/// - `{% for item in tags %}...{% end %}` -- repeats its contents for each accumulated tag, which is available as `${item}` inside
+/// - `{% for item in ${id} %}...{% end %}` -- repeats its contents for each option selected in an input, or for each entry in a group of
+///   inputs, in which case the values of the inputs in that entry are available as `${item.<input id>}` inside
+/// - `\${` and `\{%` -- a literal `${` or `{%`
+///
+/// Computed fields (from the `fields` of a workflow) can be used just like inputs, as can the built-in values `${_locale}` (the locale the
+/// workflow is in, which is empty if the app isn't translated), `${_workflow}` (the name of the workflow), and `${_tags}` (the accumulated
+/// tags, which can be looped over like the options of an input).
+///
+/// A newline directly after a block tag is removed, so blocks can be put on their own lines without leaving blank lines behind. Templates
+/// are parsed when the configuration is, so syntax errors will be caught before anything is built.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "String", into = "String")]
+pub struct Template {
+    /// The original text of the template (this is what we serialize).
+    src: String,
+    /// The parsed template.
+    nodes: Vec<Node>,
+}
+impl Template {
+    /// Parses a new template from its source text.
+    pub fn new(src: &str) -> Result<Self, TemplateError> {
+        let tokens = lex(src)?;
+        let mut parser = TemplateParser {
+            tokens: tokens.into_iter(),
+        };
+        match parser.parse_nodes()? {
+            (nodes, None) => Ok(Self {
+                src: src.to_string(),
+                nodes,
+            }),
+            // There's no block for this to close
+            (_, Some(end)) => Err(TemplateError::UnexpectedBlock {
+                block: end.to_string(),
+            }),
+        }
+    }
+    /// Renders the template with the given form values and tags.
+    pub fn render(&self, values: &HashMap<String, FormValue>, tags: &[String]) -> String {
+        self.render_with(values, tags, Escape::None)
+    }
+    /// Renders the template for use as a URL, which URL-encodes everything filled in from form values (but not the template itself).
+    pub fn render_url(&self, values: &HashMap<String, FormValue>, tags: &[String]) -> String {
+        self.render_with(values, tags, Escape::Url)
+    }
+    /// Renders the template as Markdown that will be turned into HTML on the page (like a preamble). Everything filled in from form values
+    /// (even code) is escaped so that it's shown as plain text, because those values can come from a link (through prefilling or a link to a
+    /// step), and they mustn't be able to add HTML or links to the page.
+    pub fn render_md(&self, values: &HashMap<String, FormValue>, tags: &[String]) -> String {
+        self.render_with(values, tags, Escape::Markdown)
+    }
+    /// Gets the IDs of all the inputs this template references, so they can be checked. Loop variables aren't included.
+    pub fn vars(&self) -> Vec<&str> {
+        let mut vars = Vec::new();
+        collect_vars(&self.nodes, &mut Vec::new(), &mut vars);
+        vars
+    }
+
+    fn render_with(
+        &self,
+        values: &HashMap<String, FormValue>,
+        tags: &[String],
+        escape: Escape,
+    ) -> String {
+        let mut renderer = Renderer {
+            values,
+            tags,
+            scope: Vec::new(),
+            escape,
+        };
+        let mut out = String::new();
+        renderer.render(&self.nodes, &mut out);
+        out
+    }
+}
+impl TryFrom<String> for Template {
+    type Error = TemplateError;
+
+    fn try_from(src: String) -> Result<Self, Self::Error> {
+        Self::new(&src)
+    }
+}
+impl From<Template> for String {
+    fn from(template: Template) -> Self {
+        template.src
+    }
+}
+#[cfg(feature = "schema")]
+impl JsonSchema for Template {
+    fn schema_name() -> String {
+        "Template".to_string()
+    }
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+/// The values that are always available to templates. These all start with an underscore, which input IDs and field names can't.
+pub const BUILTIN_VARS: [&str; 3] = ["_locale", "_workflow", "_tags"];
+
+/// Adds the built-in values and the given computed fields to the given form values, so they can all be used in templates. Fields are
+/// rendered from the form values and the built-ins alone, so they can't reference each other.
+pub fn add_fields(
+    values: &mut HashMap<String, FormValue>,
+    fields: &HashMap<String, Template>,
+    locale: &str,
+    workflow: &str,
+    tags: &[String],
+) {
+    values.insert("_locale".to_string(), FormValue::Single(locale.to_string()));
+    values.insert(
+        "_workflow".to_string(),
+        FormValue::Single(workflow.to_string()),
+    );
+    values.insert("_tags".to_string(), FormValue::List(tags.to_vec()));
+    let rendered: Vec<(String, FormValue)> = fields
+        .iter()
+        .map(|(name, template)| {
+            (
+                name.to_string(),
+                FormValue::Single(template.render(values, tags)),
+            )
+        })
+        .collect();
+    values.extend(rendered);
+}
+
+/// How everything filled in from a value is escaped when a template is rendered.
+#[derive(Debug, Clone, Copy)]
+enum Escape {
+    None,
+    Url,
+    /// Values are escaped so that they're shown as they are in Markdown.
+    Markdown,
+}
+
+/// A part of a parsed template.
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Var {
+        id: String,
+        filters: Vec<Filter>,
+    },
+    If {
+        cond: Condition,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        var: String,
+        over: Iterable,
+        body: Vec<Node>,
+    },
+}
+
+/// Something that can be looped over with a `for` block.
+#[derive(Debug, Clone)]
+enum Iterable {
+    Tags,
+    Var(String),
+}
+
+#[derive(Debug, Clone)]
+enum Filter {
+    Upper,
+    Code,
+    Quote,
+    List,
+    Default(String),
+}
+impl Filter {
+    /// Applies this filter to the given value. The result is always a single piece of text, because filters are for formatting.
+    fn apply(&self, value: FormValue) -> FormValue {
+        let text = value.to_string();
+        let filtered = match self {
+            Self::Upper => text.to_uppercase(),
+            // Code inputs already know how they want to be shown
+            Self::Code if matches!(value, FormValue::Code { .. }) => return value,
+            Self::Code => code(&text),
+            Self::Quote => text
+                .lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        ">".to_string()
+                    } else {
+                        format!("> {}", line)
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
+            Self::List => value
+                .items()
+                .iter()
+                .map(|v| format!("- {}", v))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            Self::Default(default) if value.is_empty() => default.to_string(),
+            Self::Default(_) => return value,
+        };
+
+        FormValue::Single(filtered)
+    }
+}
+
+/// Wraps the given value in Markdown code formatting, making sure the fence is longer than any run of backticks in the value so it can't be
+/// escaped from.
+fn code(value: &str) -> String {
+    let longest_run = longest_backtick_run(value);
+    if value.contains('\n') {
+        let fence = "`".repeat(std::cmp::max(3, longest_run + 1));
+        format!("{}\n{}\n{}", fence, value, fence)
+    } else {
+        let fence = "`".repeat(longest_run + 1);
+        // A code span that starts or ends with a backtick needs padding, otherwise that backtick would be read as part of the fence
+        if value.starts_with('`') || value.ends_with('`') {
+            format!("{} {} {}", fence, value, fence)
+        } else {
+            format!("{}{}{}", fence, value, fence)
+        }
+    }
+}
+
+/// Renders the given code as a fenced code block with the given language. If it's longer than the given number of lines, only those lines
+/// are shown, and the full code is put in a collapsible section underneath (whose toggle has the given text, which is escaped because it
+/// goes in raw HTML).
+fn code_block(
+    text: &str,
+    language: Option<&str>,
+    max_lines: Option<usize>,
+    truncated_msg: &str,
+) -> String {
+    // Pasted logs usually end with a newline, which would leave a blank line at the end of the block
+    let text = text.trim_end_matches(['\n', '\r']);
+    let fenced = |text: &str| {
+        let fence = "`".repeat(std::cmp::max(3, longest_backtick_run(text) + 1));
+        format!("{}{}\n{}\n{}", fence, language.unwrap_or(""), text, fence)
+    };
+    let lines: Vec<&str> = text.lines().collect();
+    match max_lines {
+        Some(max_lines) if lines.len() > max_lines => format!(
+            // GitHub and friends need blank lines around Markdown inside HTML
+            "{}\n\n<details>\n<summary>{}</summary>\n\n{}\n\n</details>",
+            fenced(&lines[..max_lines].join("\n")),
+            escape_html(truncated_msg),
+            fenced(text)
+        ),
+        _ => fenced(text),
+    }
+}
+
+/// Gets the length of the longest run of backticks in the given value, which any fence around it has to be longer than.
+fn longest_backtick_run(value: &str) -> usize {
+    value
+        .split(|c: char| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Escapes the characters in the given text that mean something in HTML, so that it can be put inside an element.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escapes every ASCII punctuation character in the given text with a backslash, so that it's shown as it is in Markdown (without any
+/// formatting, links, or HTML).
+fn escape_md(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_punctuation() {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Turns the given value into the text that should be put in a rendered template.
+fn interpolate(value: &FormValue) -> String {
+    match value {
+        FormValue::Code {
+            text,
+            language,
+            max_lines,
+            truncated_msg,
+        } if !text.is_empty() => code_block(text, language.as_deref(), *max_lines, truncated_msg),
+        value => value.to_string(),
+    }
+}
+
+/// Collects the IDs of all the inputs referenced in the given nodes, ignoring any loop variables in scope.
+fn collect_vars<'a>(nodes: &'a [Node], scope: &mut Vec<&'a str>, vars: &mut Vec<&'a str>) {
+    for node in nodes {
+        match node {
+            Node::Text(_) => (),
+            Node::Var { id, .. } => add_var(id, scope, vars),
+            Node::If {
+                cond,
+                then,
+                otherwise,
+            } => {
+                for id in cond.vars() {
+                    add_var(id, scope, vars);
+                }
+                collect_vars(then, scope, vars);
+                collect_vars(otherwise, scope, vars);
+            }
+            Node::For { var, over, body } => {
+                if let Iterable::Var(id) = over {
+                    add_var(id, scope, vars);
+                }
+                scope.push(var);
+                collect_vars(body, scope, vars);
+                scope.pop();
+            }
+        }
+    }
+}
+
+fn add_var<'a>(id: &'a str, scope: &[&str], vars: &mut Vec<&'a str>) {
+    // The values in an entry of a group are accessed through the loop variable (e.g. `step.description`)
+    let var = id.split('.').next().unwrap_or(id);
+    if !scope.contains(&var) && !vars.contains(&id) {
+        vars.push(id);
+    }
+}
+
+struct Renderer<'a> {
+    values: &'a HashMap<String, FormValue>,
+    tags: &'a [String],
+    /// The loop variables currently in scope, innermost last.
+    scope: Vec<(String, FormValue)>,
+    /// How everything filled in from a value is escaped.
+    escape: Escape,
+}
+impl<'a> Renderer<'a> {
+    fn value(&self, id: &str) -> Option<FormValue> {
+        // Loop variables shadow inputs
+        self.scope
+            .iter()
+            .rev()
+            .find(|(var, _)| *var == id)
+            .map(|(_, value)| value.clone())
+            .or_else(|| self.values.get(id).cloned())
+    }
+    fn render(&mut self, nodes: &'a [Node], out: &mut String) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Var { id, filters } => {
+                    let mut value = self
+                        .value(id)
+                        .unwrap_or_else(|| FormValue::Single(String::new()));
+                    for filter in filters {
+                        value = filter.apply(value);
+                    }
+                    let text = interpolate(&value);
+                    match self.escape {
+                        Escape::None => out.push_str(&text),
+                        Escape::Url => out.push_str(&urlencoding::encode(&text)),
+                        Escape::Markdown => out.push_str(&escape_md(&text)),
+                    }
+                }
+                Node::If {
+                    cond,
+                    then,
+                    otherwise,
+                } => {
+                    let branch = if cond.eval(self.tags, |id| self.value(id)) {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    self.render(branch, out);
+                }
+                Node::For { var, over, body } => {
+                    // Each iteration binds the loop variable, and, for an entry in a group, each of the entry's values
+                    let iterations: Vec<Vec<(String, FormValue)>> = match over {
+                        Iterable::Tags => self
+                            .tags
+                            .iter()
+                            .map(|tag| vec![(var.to_string(), FormValue::Single(tag.to_string()))])
+                            .collect(),
+                        Iterable::Var(id) => match self.value(id) {
+                            Some(FormValue::Group(entries)) => entries
+                                .into_iter()
+                                .map(|entry| {
+                                    let mut bindings = vec![(
+                                        var.to_string(),
+                                        FormValue::Single(entry.to_string()),
+                                    )];
+                                    bindings.extend(entry.0.into_iter().map(
+                                        |(input_id, value)| {
+                                            (format!("{}.{}", var, input_id), value)
+                                        },
+                                    ));
+                                    bindings
+                                })
+                                .collect(),
+                            value => value
+                                .map(|v| v.items())
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(|item| vec![(var.to_string(), FormValue::Single(item))])
+                                .collect(),
+                        },
+                    };
+                    for bindings in iterations {
+                        let scope_len = self.scope.len();
+                        self.scope.extend(bindings);
+                        self.render(body, out);
+                        self.scope.truncate(scope_len);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Text(String),
+    /// The inside of a `${...}` tag.
+    Var(String),
+    /// The inside of a `{% ... %}` tag, trimmed.
+    Block(String),
+}
+
+fn lex(src: &str) -> Result<Vec<Token>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = src;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("\\${") || rest.starts_with("\\{%") {
+            text.push_str(&rest[1..3]);
+            rest = &rest[3..];
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let end = find_close(after, "}").ok_or_else(|| unterminated(rest))?;
+            if !text.is_empty() {
+                tokens.push(Token::Text(std::mem::take(&mut text)));
+            }
+            tokens.push(Token::Var(after[..end].to_string()));
+            rest = &after[end + 1..];
+        } else if let Some(after) = rest.strip_prefix("{%") {
+            let end = find_close(after, "%}").ok_or_else(|| unterminated(rest))?;
+            if !text.is_empty() {
+                tokens.push(Token::Text(std::mem::take(&mut text)));
+            }
+            tokens.push(Token::Block(after[..end].trim().to_string()));
+            rest = &after[end + 2..];
+            if let Some(after_newline) = rest
+                .strip_prefix('\n')
+                .or_else(|| rest.strip_prefix("\r\n"))
+            {
+                rest = after_newline;
+            }
+        } else {
+            text.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+
+    Ok(tokens)
+}
+
+fn unterminated(rest: &str) -> TemplateError {
+    TemplateError::UnterminatedTag {
+        tag: rest.chars().take(20).collect(),
+    }
+}
+
+/// Finds the given closing delimiter, skipping over anything in double quotes.
+fn find_close(s: &str, close: &str) -> Option<usize> {
+    let mut in_str = false;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if in_str {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_str = false;
+            }
+        } else if c == '"' {
+            in_str = true;
+        } else if s[i..].starts_with(close) {
+            return Some(i);
+        }
+    }
+
+    None
+}
+
+/// Splits the given text at every `|` that isn't in double quotes.
+fn split_pipes(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = s;
+    while let Some(i) = find_close(rest, "|") {
+        parts.push(&rest[..i]);
+        rest = &rest[i + 1..];
+    }
+    parts.push(rest);
+
+    parts
+}
+
+/// Parses a double-quoted string literal.
+fn parse_str(s: &str) -> Option<String> {
+    let inner = s.strip_prefix('"')?.strip_suffix('"')?;
+    let mut parsed = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => parsed.push(chars.next()?),
+            // An unescaped quote would have ended the string
+            '"' => return None,
+            c => parsed.push(c),
+        }
+    }
+
+    Some(parsed)
+}
+
+/// The tag that ended a list of nodes.
+#[derive(Debug, Clone, Copy)]
+enum BlockEnd {
+    Else,
+    End,
+}
+impl std::fmt::Display for BlockEnd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Else => write!(f, "else"),
+            Self::End => write!(f, "end"),
+        }
+    }
+}
+
+struct TemplateParser {
+    tokens: std::vec::IntoIter<Token>,
+}
+impl TemplateParser {
+    /// Parses nodes until the end of the template or an `else`/`end` tag, which is returned.
+    fn parse_nodes(&mut self) -> Result<(Vec<Node>, Option<BlockEnd>), TemplateError> {
+        let mut nodes = Vec::new();
+        while let Some(token) = self.tokens.next() {
+            match token {
+                Token::Text(text) => nodes.push(Node::Text(text)),
+                Token::Var(tag) => nodes.push(parse_var(&tag)?),
+                Token::Block(block) if block == "else" => return Ok((nodes, Some(BlockEnd::Else))),
+                Token::Block(block) if block == "end" => return Ok((nodes, Some(BlockEnd::End))),
+                Token::Block(block) => {
+                    if let Some(cond) = block.strip_prefix("if ") {
+                        let cond = Condition::new(cond.trim()).map_err(|source| {
+                            TemplateError::InvalidCondition {
+                                block: block.to_string(),
+                                source,
+                            }
+                        })?;
+                        let (then, end) = self.parse_nodes()?;
+                        let otherwise = match end {
+                            Some(BlockEnd::End) => Vec::new(),
+                            Some(BlockEnd::Else) => self.parse_body(&block)?,
+                            None => return Err(TemplateError::UnclosedBlock { block }),
+                        };
+                        nodes.push(Node::If {
+                            cond,
+                            then,
+                            otherwise,
+                        });
+                    } else if let Some(rest) = block.strip_prefix("for ") {
+                        let (var, over) =
+                            parse_for(rest).ok_or_else(|| TemplateError::InvalidBlock {
+                                block: block.to_string(),
+                            })?;
+                        let body = self.parse_body(&block)?;
+                        nodes.push(Node::For { var, over, body });
+                    } else {
+                        return Err(TemplateError::InvalidBlock { block });
+                    }
+                }
+            }
+        }
+
+        Ok((nodes, None))
+    }
+    /// Parses the nodes in a block that must be closed with an `end` tag (with no `else`).
+    fn parse_body(&mut self, block: &str) -> Result<Vec<Node>, TemplateError> {
+        match self.parse_nodes()? {
+            (body, Some(BlockEnd::End)) => Ok(body),
+            (_, Some(BlockEnd::Else)) => Err(TemplateError::UnexpectedBlock {
+                block: BlockEnd::Else.to_string(),
+            }),
+            (_, None) => Err(TemplateError::UnclosedBlock {
+                block: block.to_string(),
+            }),
+        }
+    }
+}
+
+/// Parses the inside of a `${...}` tag.
+fn parse_var(tag: &str) -> Result<Node, TemplateError> {
+    let mut parts = split_pipes(tag).into_iter();
+    // There's always at least one part
+    let id = parts.next().unwrap().trim();
+    if id.is_empty() {
+        return Err(TemplateError::InvalidTag {
+            tag: tag.to_string(),
+        });
+    }
+    let filters = parts
+        .map(|filter| parse_filter(filter.trim(), tag))
+        .collect::<Result<Vec<Filter>, TemplateError>>()?;
+
+    Ok(Node::Var {
+        id: id.to_string(),
+        filters,
+    })
+}
+
+fn parse_filter(filter: &str, tag: &str) -> Result<Filter, TemplateError> {
+    let invalid_args = || TemplateError::InvalidFilterArgs {
+        filter: filter.to_string(),
+        tag: tag.to_string(),
+    };
+    let (name, arg) = match filter.find('(') {
+        Some(open) => {
+            let arg = filter[open + 1..]
+                .strip_suffix(')')
+                .and_then(|arg| parse_str(arg.trim()))
+                .ok_or_else(invalid_args)?;
+            (filter[..open].trim(), Some(arg))
+        }
+        None => (filter, None),
+    };
+    match (name, arg) {
+        ("upper", None) => Ok(Filter::Upper),
+        ("code", None) => Ok(Filter::Code),
+        ("quote", None) => Ok(Filter::Quote),
+        ("list", None) => Ok(Filter::List),
+        ("default", Some(default)) => Ok(Filter::Default(default)),
+        ("upper" | "code" | "quote" | "list" | "default", _) => Err(invalid_args()),
+        _ => Err(TemplateError::UnknownFilter {
+            filter: name.to_string(),
+            tag: tag.to_string(),
+        }),
+    }
+}
+
+/// Parses the part of a `for` block after `for`.
+fn parse_for(rest: &str) -> Option<(String, Iterable)> {
+    let (var, over) = rest.split_once(" in ")?;
+    let var = var.trim();
+    if var.is_empty() || !var.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    let over = match over.trim() {
+        "tags" => Iterable::Tags,
+        over => Iterable::Var(
+            over.strip_prefix("${")?
+                .strip_suffix('}')?
+                .trim()
+                .to_string(),
+        ),
+    };
+
+    Some((var.to_string(), over))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::form::GroupEntry;
+
+    fn render(src: &str, values: &[(&str, FormValue)], tags: &[&str]) -> String {
+        let values: HashMap<String, FormValue> = values
+            .iter()
+            .map(|(id, value)| (id.to_string(), value.clone()))
+            .collect();
+        let tags: Vec<String> = tags.iter().map(|tag| tag.to_string()).collect();
+        Template::new(src).unwrap().render(&values, &tags)
+    }
+    fn single(text: &str) -> FormValue {
+        FormValue::Single(text.to_string())
+    }
+    fn list(items: &[&str]) -> FormValue {
+        FormValue::List(items.iter().map(|item| item.to_string()).collect())
+    }
+
+    #[test]
+    fn interpolates_values() {
+        assert_eq!(
+            render("Hello, ${name}!", &[("name", single("world"))], &[]),
+            "Hello, world!"
+        );
+        // Inputs that haven't been filled out are empty
+        assert_eq!(render("[${missing}]", &[], &[]), "[]");
+    }
+    #[test]
+    fn escapes_are_literal() {
+        assert_eq!(
+            render(r"\${name} \{% end %}", &[("name", single("x"))], &[]),
+            "${name} {% end %}"
+        );
+    }
+    #[test]
+    fn filters_are_applied_in_order() {
+        let value = [("v", single("a\nb"))];
+        assert_eq!(render("${v | quote}", &value, &[]), "> a\n> b");
+        assert_eq!(render("${v | upper | quote}", &value, &[]), "> A\n> B");
+        assert_eq!(
+            render("${v | list}", &[("v", list(&["x, y", "z"]))], &[]),
+            "- x, y\n- z"
+        );
+        assert_eq!(render(r#"${v | default("none")}"#, &[], &[]), "none");
+        assert_eq!(
+            render(r#"${v | default("a | b")}"#, &[("v", single("set"))], &[]),
+            "set"
+        );
+    }
+    #[test]
+    fn code_filter_fence_cant_be_escaped() {
+        let value = |text: &str| [("v", single(text))];
+        assert_eq!(render("${v | code}", &value("x"), &[]), "`x`");
+        assert_eq!(render("${v | code}", &value("a `b` c"), &[]), "``a `b` c``");
+        // A leading or trailing backtick would otherwise merge with the fence
+        assert_eq!(render("${v | code}", &value("`b"), &[]), "`` `b ``");
+        assert_eq!(
+            render("${v | code}", &value("a\n````\nb"), &[]),
+            "`````\na\n````\nb\n`````"
+        );
+        assert_eq!(render("${v | code}", &value("a\nb"), &[]), "```\na\nb\n```");
+    }
+    #[test]
+    fn newlines_after_blocks_are_trimmed() {
+        assert_eq!(
+            render(
+                "a\n{% if ${v} %}\nb\n{% end %}\nc",
+                &[("v", single("x"))],
+                &[]
+            ),
+            "a\nb\nc"
+        );
+        assert_eq!(
+            render("a\r\n{% if ${v} %}\r\nb\r\n{% end %}\r\nc", &[], &[]),
+            "a\r\nc"
+        );
+    }
+    #[test]
+    fn if_else_picks_a_branch() {
+        let src = r#"{% if tags contains "C:bug" %}bug{% else %}other{% end %}"#;
+        assert_eq!(render(src, &[], &["C:bug"]), "bug");
+        assert_eq!(render(src, &[], &[]), "other");
+    }
+    #[test]
+    fn loops_over_tags_and_options() {
+        assert_eq!(
+            render("{% for t in tags %}[${t}]{% end %}", &[], &["a", "b"]),
+            "[a][b]"
+        );
+        assert_eq!(
+            render(
+                "{% for o in ${opts} %}<${o}>{% end %}",
+                &[("opts", list(&["x, y", "z"]))],
+                &[]
+            ),
+            "<x, y><z>"
+        );
+        // A single value is looped over once, and an empty one not at all
+        assert_eq!(
+            render(
+                "{% for o in ${v} %}<${o}>{% end %}",
+                &[("v", single("x"))],
+                &[]
+            ),
+            "<x>"
+        );
+        assert_eq!(render("{% for o in ${v} %}<${o}>{% end %}", &[], &[]), "");
+    }
+    #[test]
+    fn loops_over_group_entries() {
+        let steps = FormValue::Group(vec![
+            GroupEntry(vec![
+                ("action".to_string(), single("Clicked")),
+                ("result".to_string(), single("Crashed")),
+            ]),
+            GroupEntry(vec![
+                ("action".to_string(), single("Waited")),
+                ("result".to_string(), single("")),
+            ]),
+        ]);
+        assert_eq!(
+            render(
+                "{% for step in ${steps} %}${step.action}: ${step.result | default(\"nothing\")}\n{% end %}",
+                &[("steps", steps)],
+                &[]
+            ),
+            "Clicked: Crashed\nWaited: nothing\n"
+        );
+    }
+    #[test]
+    fn nested_blocks() {
+        let src = "{% for o in ${opts} %}{% if ${o} == \"b\" %}[${o}]{% else %}{% for t in tags %}${o}${t}{% end %}{% end %}{% end %}";
+        assert_eq!(
+            render(src, &[("opts", list(&["a", "b"]))], &["1", "2"]),
+            "a1a2[b]"
+        );
+    }
+    #[test]
+    fn loop_variables_shadow_inputs() {
+        let values = [("x", single("input")), ("opts", list(&["a", "b"]))];
+        assert_eq!(
+            render("${x}{% for x in ${opts} %}${x}{% end %}${x}", &values, &[]),
+            "inputabinput"
+        );
+        // The innermost loop wins, and the outer one is back afterwards
+        assert_eq!(
+            render(
+                "{% for x in ${opts} %}{% for x in tags %}${x}{% end %}${x}{% end %}",
+                &values,
+                &["t"]
+            ),
+            "tatb"
+        );
+    }
+    #[test]
+    fn markdown_rendering_escapes_values() {
+        let values: HashMap<String, FormValue> =
+            vec![("v".to_string(), single("<b>[x](javascript:alert(1))</b>"))]
+                .into_iter()
+                .collect();
+        let template = Template::new("**${v}** ${v | code}").unwrap();
+        assert_eq!(
+            template.render_md(&values, &[]),
+            r"**\<b\>\[x\]\(javascript\:alert\(1\)\)\<\/b\>** \`\<b\>\[x\]\(javascript\:alert\(1\)\)\<\/b\>\`"
+        );
+        assert_eq!(
+            template.render_url(&values, &[]),
+            "**%3Cb%3E%5Bx%5D%28javascript%3Aalert%281%29%29%3C%2Fb%3E** %60%3Cb%3E%5Bx%5D%28javascript%3Aalert%281%29%29%3C%2Fb%3E%60"
+        );
+    }
+    #[test]
+    fn vars_ignore_loop_variables() {
+        let template = Template::new(
+            "${a}{% for s in ${steps} %}${s.action}${b}{% end %}{% if ${c} %}{% end %}${a}",
+        )
+        .unwrap();
+        assert_eq!(template.vars(), vec!["a", "steps", "b", "c"]);
+    }
+    #[test]
+    fn syntax_errors_are_caught() {
+        let err = |src: &str| Template::new(src).unwrap_err();
+        assert!(matches!(
+            err("${name"),
+            TemplateError::UnterminatedTag { .. }
+        ));
+        assert!(matches!(
+            err("{% if ${a} "),
+            TemplateError::UnterminatedTag { .. }
+        ));
+        assert!(matches!(err("${}"), TemplateError::InvalidTag { .. }));
+        assert!(matches!(
+            err("${a | shout}"),
+            TemplateError::UnknownFilter { .. }
+        ));
+        assert!(matches!(
+            err("${a | upper(\"x\")}"),
+            TemplateError::InvalidFilterArgs { .. }
+        ));
+        assert!(matches!(
+            err("${a | default}"),
+            TemplateError::InvalidFilterArgs { .. }
+        ));
+        assert!(matches!(
+            err("${a | default(x)}"),
+            TemplateError::InvalidFilterArgs { .. }
+        ));
+        assert!(matches!(
+            err("{% while ${a} %}{% end %}"),
+            TemplateError::InvalidBlock { .. }
+        ));
+        assert!(matches!(
+            err("{% for in tags %}{% end %}"),
+            TemplateError::InvalidBlock { .. }
+        ));
+        assert!(matches!(
+            err("{% for x in y %}{% end %}"),
+            TemplateError::InvalidBlock { .. }
+        ));
+        assert!(matches!(
+            err("{% if ${a} == %}{% end %}"),
+            TemplateError::InvalidCondition { .. }
+        ));
+    }
+    #[test]
+    fn blocks_must_match() {
+        let err = |src: &str| Template::new(src).unwrap_err();
+        assert!(matches!(
+            err("a{% end %}"),
+            TemplateError::UnexpectedBlock { .. }
+        ));
+        assert!(matches!(
+            err("{% else %}"),
+            TemplateError::UnexpectedBlock { .. }
+        ));
+        assert!(matches!(
+            err("{% if ${a} %}{% end %}{% end %}"),
+            TemplateError::UnexpectedBlock { .. }
+        ));
+        assert!(matches!(
+            err("{% for t in tags %}{% else %}{% end %}"),
+            TemplateError::UnexpectedBlock { .. }
+        ));
+        assert!(matches!(
+            err("{% if ${a} %}{% else %}{% else %}{% end %}"),
+            TemplateError::UnexpectedBlock { .. }
+        ));
+        assert!(matches!(
+            err("{% if ${a} %}"),
+            TemplateError::UnclosedBlock { .. }
+        ));
+        assert!(matches!(
+            err("{% if ${a} %}{% else %}"),
+            TemplateError::UnclosedBlock { .. }
+        ));
+        assert!(matches!(
+            err("{% for t in tags %}{% if ${a} %}{% end %}"),
+            TemplateError::UnclosedBlock { .. }
+        ));
+    }
+    #[test]
+    fn quotes_in_tags_are_skipped_over() {
+        // The `}` and `|` in the string don't end the tag or start a filter
+        assert_eq!(
+            render(r#"${v | default("} | \" ")}"#, &[], &[]),
+            r#"} | " "#
+        );
+    }
+    #[test]
+    fn code_blocks_are_fenced() {
+        assert_eq!(
+            code_block("fn main() {}\n\n", Some("rust"), None, "Full output"),
+            "```rust\nfn main() {}\n```"
+        );
+        assert_eq!(
+            code_block("a\r\nb", None, Some(5), "Full output"),
+            "```\na\r\nb\n```"
+        );
+        // The fence has to be longer than any run of backticks in the code
+        assert_eq!(
+            code_block("````\n`", None, None, "Full output"),
+            "`````\n````\n`\n`````"
+        );
+    }
+    #[test]
+    fn long_code_blocks_are_truncated() {
+        assert_eq!(
+            code_block("1\n2\n3", Some("console"), Some(2), "<b>All</b> & more"),
+            "```console\n1\n2\n```\n\n<details>\n<summary>&lt;b&gt;All&lt;/b&gt; &amp; more</summary>\n\n```console\n1\n2\n3\n```\n\n</details>"
+        );
+    }
+    #[test]
+    fn finds_longest_backtick_run() {
+        assert_eq!(longest_backtick_run(""), 0);
+        assert_eq!(longest_backtick_run("no ticks"), 0);
+        assert_eq!(longest_backtick_run("`a` ``b`` `"), 2);
+        assert_eq!(longest_backtick_run("```"), 3);
+    }
+}
@@ -0,0 +1,24 @@
+use std::env;
+
+use perseus::RenderFnResult;
+
+use crate::{parser::Config, templates::paths::build_path};
+
+pub async fn get_build_paths() -> RenderFnResult<Vec<String>> {
+    let root_cfg_file_path =
+        env::var("TRIBBLE_CONF").unwrap_or_else(|_| "../../../examples/basic.yml".to_string());
+    let root_cfg = Config::new(&root_cfg_file_path)?;
+
+    Ok(index_paths(&root_cfg))
+}
+
+/// Gets the paths of the index pages for the given root config file.
+// The root of the site is the empty path, and, if we're using i18n, each language gets its own list of workflows underneath it
+pub fn index_paths(root_cfg: &Config) -> Vec<String> {
+    let mut paths = vec![String::new()];
+    if let Config::Root { languages, .. } = root_cfg {
+        paths.extend(languages.keys().map(|locale| build_path(locale, "")));
+    }
+
+    paths
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::env;
+
+use perseus::RenderFnResultWithCause;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::ParserError,
+    parser::{Config, LandingText, Workflow},
+    templates::workflow::parse_md::parse_md_to_html,
+};
+
+#[derive(Serialize, Deserialize)]
+pub enum IndexProps {
+    /// A list of all the workflows in one language.
+    Workflows {
+        /// The locale the workflows are in, which is empty if the app isn't translated.
+        locale: String,
+        title: String,
+        /// The text shown above the list, already rendered to HTML.
+        text: String,
+        /// The workflows, in the order they should be listed in.
+        workflows: Vec<WorkflowSummary>,
+    },
+    /// The root of a translated app, which sends the user to the list of workflows in their language.
+    Languages {
+        /// All the supported locales, in alphabetical order.
+        locales: Vec<String>,
+        /// The locale to use if none of the user's preferred languages are supported.
+        default_locale: String,
+    },
+}
+
+/// The details of a workflow that are shown on the landing page.
+#[derive(Serialize, Deserialize)]
+pub struct WorkflowSummary {
+    /// The name of the workflow, which is what its page is at.
+    pub name: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub icon: Option<String>,
+}
+
+#[perseus::autoserde(build_state)]
+pub async fn get_build_state(
+    path: String,
+    _locale: String, // We use our own purpose-built i18n system
+) -> RenderFnResultWithCause<IndexProps> {
+    // The root page has the bare template path, and language pages are underneath it
+    let locale = path
+        .strip_prefix("index")
+        .unwrap_or(&path)
+        .trim_start_matches('/');
+
+    let root_cfg_path =
+        env::var("TRIBBLE_CONF").unwrap_or_else(|_| "../../../examples/basic.yml".to_string());
+    let root_cfg = Config::new(&root_cfg_path)?;
+
+    Ok(index_props(root_cfg, &root_cfg_path, locale, Config::new)?)
+}
+
+/// Gets the props for the index page in the given locale (empty for the root of the site) from the given root config file, using the given
+/// function to load the language config file for that locale if the app is translated.
+fn index_props(
+    root_cfg: Config,
+    root_cfg_path: &str,
+    locale: &str,
+    load: impl Fn(&str) -> Result<Config, ParserError>,
+) -> Result<IndexProps, ParserError> {
+    let (workflows, landing) = match root_cfg {
+        Config::Root { ref languages, .. } => {
+            if locale.is_empty() {
+                let default_locale = match root_cfg.reference_locale() {
+                    Some(locale) => locale.to_string(),
+                    None => {
+                        return Err(ParserError::NoLanguages {
+                            filename: root_cfg_path.to_string(),
+                        })
+                    }
+                };
+                let mut locales: Vec<String> = languages.keys().cloned().collect();
+                locales.sort();
+                return Ok(IndexProps::Languages {
+                    locales,
+                    default_locale,
+                });
+            }
+            // These paths come from the same file, so this would only happen if it changed while we were building
+            let lang_cfg_path = match languages.get(locale) {
+                Some(path) => path,
+                None => {
+                    return Err(ParserError::NoSuchLocale {
+                        filename: root_cfg_path.to_string(),
+                        locale: locale.to_string(),
+                    })
+                }
+            };
+            match load(lang_cfg_path)? {
+                Config::Language {
+                    workflows, landing, ..
+                } => (workflows, landing),
+                Config::Root { .. } => {
+                    return Err(ParserError::RootLinksToRoot {
+                        filename: root_cfg_path.to_string(),
+                        linked: lang_cfg_path.to_string(),
+                    })
+                }
+            }
+        }
+        Config::Language {
+            workflows, landing, ..
+        } => (workflows, landing),
+    };
+    let LandingText { title, text } = landing;
+
+    Ok(IndexProps::Workflows {
+        locale: locale.to_string(),
+        title,
+        text: parse_md_to_html(&text),
+        workflows: summarize(workflows),
+    })
+}
+
+/// Gets the details of the given workflows that are shown on the landing page, in the order they should be listed in.
+fn summarize(workflows: HashMap<String, Workflow>) -> Vec<WorkflowSummary> {
+    let mut workflows: Vec<(String, Workflow)> = workflows.into_iter().collect();
+    // Workflows without an order go after all the others
+    workflows.sort_by(|(_, a), (_, b)| {
+        (a.order.is_none(), a.order, &a.title).cmp(&(b.order.is_none(), b.order, &b.title))
+    });
+    workflows
+        .into_iter()
+        .map(|(name, workflow)| WorkflowSummary {
+            name,
+            title: workflow.title,
+            description: workflow.description,
+            icon: workflow.icon,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::{
+        index::get_build_paths::index_paths,
+        paths::{build_path, page_link},
+    };
+
+    const LANG_CFG: &str = r#"
+landing:
+  title: Report an issue
+  text: "Pick **one**:"
+workflows:
+  bugs:
+    title: Report a bug
+    order: 1
+    index: Start
+    sections:
+      Start:
+        - "Thanks!"
+    endpoints: {}
+  ideas:
+    title: Suggest an idea
+    index: Start
+    sections:
+      Start:
+        - "Thanks!"
+    endpoints: {}
+"#;
+    const ROOT_CFG: &str = r#"
+languages:
+  fr-FR: fr-FR.yml
+  en-US: en-US.yml
+reference_locale: en-US
+"#;
+
+    fn cfg(yaml: &str) -> Config {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+    fn props(root_cfg: &str, locale: &str) -> IndexProps {
+        index_props(cfg(root_cfg), "tribble.yml", locale, |path| {
+            assert!(path == "en-US.yml" || path == "fr-FR.yml");
+            Ok(cfg(LANG_CFG))
+        })
+        .unwrap()
+    }
+    /// Gets the links to all the workflows in the given props, in order.
+    fn workflow_links(props: IndexProps) -> Vec<String> {
+        match props {
+            IndexProps::Workflows {
+                locale, workflows, ..
+            } => workflows
+                .iter()
+                .map(|workflow| page_link("workflow", &locale, &workflow.name))
+                .collect(),
+            IndexProps::Languages { .. } => panic!("expected a list of workflows"),
+        }
+    }
+
+    #[test]
+    fn untranslated_apps_only_have_a_root_list() {
+        assert_eq!(index_paths(&cfg(LANG_CFG)), vec![""]);
+        let props = props(LANG_CFG, "");
+        match &props {
+            IndexProps::Workflows {
+                locale,
+                title,
+                text,
+                ..
+            } => {
+                assert_eq!(locale, "");
+                assert_eq!(title, "Report an issue");
+                assert_eq!(text, "<p>Pick <strong>one</strong>:</p>\n");
+            }
+            IndexProps::Languages { .. } => {
+                panic!("an untranslated app shouldn't have a language picker")
+            }
+        }
+        assert_eq!(
+            workflow_links(props),
+            vec!["workflow/bugs", "workflow/ideas"]
+        );
+    }
+
+    #[test]
+    fn translated_apps_have_a_list_for_each_language() {
+        let mut paths = index_paths(&cfg(ROOT_CFG));
+        paths.sort();
+        assert_eq!(paths, vec!["", "en-US", "fr-FR"]);
+        // The root of the site sends the user to their language
+        match props(ROOT_CFG, "") {
+            IndexProps::Languages {
+                locales,
+                default_locale,
+            } => {
+                assert_eq!(locales, vec!["en-US", "fr-FR"]);
+                assert_eq!(default_locale, "en-US");
+                let links: Vec<String> = locales
+                    .iter()
+                    .map(|locale| page_link("index", locale, ""))
+                    .collect();
+                assert_eq!(links, vec!["index/en-US", "index/fr-FR"]);
+            }
+            IndexProps::Workflows { .. } => panic!("expected a language picker"),
+        }
+        assert_eq!(
+            workflow_links(props(ROOT_CFG, "fr-FR")),
+            vec!["workflow/fr-FR/bugs", "workflow/fr-FR/ideas"]
+        );
+        // Locales that aren't in the root config file are errors
+        assert!(matches!(
+            index_props(cfg(ROOT_CFG), "tribble.yml", "de-DE", |_| Ok(cfg(LANG_CFG))),
+            Err(ParserError::NoSuchLocale { .. })
+        ));
+    }
+
+    #[test]
+    fn links_match_build_paths() {
+        assert_eq!(page_link("index", "", ""), "");
+        assert_eq!(build_path("", "bugs"), "bugs");
+        assert_eq!(build_path("en-US", "bugs"), "en-US/bugs");
+        assert_eq!(build_path("en-US", ""), "en-US");
+    }
+}
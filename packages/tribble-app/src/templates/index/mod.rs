@@ -0,0 +1,26 @@
+mod get_build_paths;
+mod get_build_state;
+mod view;
+
+use perseus::{Html, SsrNode, Template};
+use sycamore::view::View;
+
+pub fn get_template<G: Html>() -> Template<G> {
+    Template::new("index")
+        .template(view::index)
+        .head(head)
+        .build_state_fn(get_build_state::get_build_state)
+        .build_paths_fn(get_build_paths::get_build_paths)
+}
+
+#[perseus::head]
+fn head(props: get_build_state::IndexProps) -> View<SsrNode> {
+    let title = match props {
+        get_build_state::IndexProps::Workflows { title, .. } => title,
+        // This page is only ever seen for a moment before the user is redirected
+        get_build_state::IndexProps::Languages { .. } => "Tribble".to_string(),
+    };
+    sycamore::view! {
+        title { (title) }
+    }
+}
@@ -0,0 +1,138 @@
+use super::get_build_state::{IndexProps, WorkflowSummary};
+use crate::templates::paths::page_link;
+use sycamore::prelude::*;
+
+#[perseus::template(Index)]
+#[component(Index<G>)]
+pub fn index(props: IndexProps) -> View<G> {
+    let contents = match props {
+        IndexProps::Workflows {
+            locale,
+            text,
+            workflows,
+            ..
+        } => {
+            let workflows = View::new_fragment(
+                workflows
+                    .into_iter()
+                    .map(|workflow| render_workflow(&locale, workflow))
+                    .collect(),
+            );
+            view! {
+                div(class = "markdown mb-4", dangerously_set_inner_html = &text) {}
+                ul(class = "workflow-list") {
+                    (workflows)
+                }
+            }
+        }
+        IndexProps::Languages {
+            locales,
+            default_locale,
+        } => {
+            // We send the user to the list for their language straight away (there are no preferences on the server)
+            if G::IS_BROWSER {
+                let locale = preferred_locale(&locales).unwrap_or(default_locale);
+                let _ = web_sys::window()
+                    .unwrap()
+                    .location()
+                    .replace(&page_link("index", &locale, ""));
+            }
+            // If that doesn't work for some reason, the user can pick a language themselves
+            let links = View::new_fragment(
+                locales
+                    .into_iter()
+                    .map(|locale| {
+                        let href = page_link("index", &locale, "");
+                        view! {
+                            li {
+                                a(href = href, class = "workflow-link") { (locale) }
+                            }
+                        }
+                    })
+                    .collect(),
+            );
+            view! {
+                ul(class = "workflow-list") {
+                    (links)
+                }
+            }
+        }
+    };
+
+    view! {
+        div(class = "flex justify-center w-full min-h-full py-2 xs:py-6 sm:py-8 md:py-16") {
+            main(class = "section-container xs:shadow-md dark:xs:shadow-lg xs:rounded-lg text-center flex-col md:w-[48rem] m-auto p-4") {
+                (contents)
+            }
+        }
+    }
+}
+
+/// Renders a link to the given workflow, which is in the given locale (empty if the app isn't translated).
+fn render_workflow<G: GenericNode>(
+    locale: &str,
+    WorkflowSummary {
+        name,
+        title,
+        description,
+        icon,
+    }: WorkflowSummary,
+) -> View<G> {
+    let href = page_link("workflow", locale, &name);
+    let icon = match icon {
+        // Anything that looks like a path is an image, and anything else is shown as it is (which works for emojis)
+        Some(icon) if icon.contains('/') => view! {
+            img(src = icon, alt = "", class = "workflow-icon") {}
+        },
+        Some(icon) => view! {
+            span(aria-hidden = "true", class = "workflow-icon") { (icon) }
+        },
+        None => View::empty(),
+    };
+    let description = match description {
+        Some(description) => view! {
+            p(class = "workflow-description") { (description) }
+        },
+        None => View::empty(),
+    };
+
+    view! {
+        li {
+            a(href = href, class = "workflow-link") {
+                (icon)
+                div {
+                    span(class = "workflow-title") { (title) }
+                    (description)
+                }
+            }
+        }
+    }
+}
+
+/// Finds the first of the user's preferred languages (as set in their browser) that's supported, falling back to a supported locale for
+/// the same language in another region (e.g. `en-GB` for `en-US`). This must only be called in the browser.
+fn preferred_locale(locales: &[String]) -> Option<String> {
+    let preferred: Vec<String> = web_sys::window()
+        .unwrap()
+        .navigator()
+        .languages()
+        .iter()
+        .filter_map(|lang| lang.as_string())
+        .collect();
+    let language = |locale: &str| locale.split('-').next().unwrap_or("").to_lowercase();
+    preferred
+        .iter()
+        .find_map(|lang| {
+            locales
+                .iter()
+                .find(|locale| locale.eq_ignore_ascii_case(lang))
+        })
+        .or_else(|| {
+            preferred.iter().find_map(|lang| {
+                locales
+                    .iter()
+                    .find(|locale| language(locale) == language(lang))
+            })
+        })
+        .cloned()
+}
@@ -1 +1,3 @@
+pub mod index;
+mod paths;
 pub mod workflow;
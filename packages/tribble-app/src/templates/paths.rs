@@ -0,0 +1,21 @@
+/// Gets the path a page is generated at within its template, from the locale it's in (empty if the app isn't translated) and its name
+/// within that locale (e.g. the name of a workflow, or empty for the list of workflows).
+pub fn build_path(locale: &str, name: &str) -> String {
+    [locale, name]
+        .iter()
+        .filter(|seg| !seg.is_empty())
+        .copied()
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+/// Gets a link to a page generated from the given template (see `build_path`). This has no leading slash, so it's resolved against the
+/// document's base URL, which Perseus sets to the root of the site (including any base path), no matter which page the link is on.
+pub fn page_link(template: &str, locale: &str, name: &str) -> String {
+    match build_path(locale, name) {
+        // The root of the index template is the root of the site, which an empty link resolves to
+        path if path.is_empty() && template == "index" => String::new(),
+        path if path.is_empty() => template.to_string(),
+        path => format!("{}/{}", template, path),
+    }
+}
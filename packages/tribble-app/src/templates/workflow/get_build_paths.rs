@@ -2,10 +2,9 @@ use std::env;
 
 use perseus::RenderFnResult;
 
-use crate::{errors::ParserError, parser::Config};
+use crate::{errors::ParserError, parser::Config, templates::paths::build_path};
 
 // Note: for a workflow named `index`, we'll generate a path literally called `index`
-// TODO maybe generatea  root page for selecting workflows?
 pub async fn get_build_paths() -> RenderFnResult<Vec<String>> {
     // We need a root config file to work with
     // With the CLI, this variable will always be defined
@@ -14,13 +13,22 @@ pub async fn get_build_paths() -> RenderFnResult<Vec<String>> {
         env::var("TRIBBLE_CONF").unwrap_or_else(|_| "../../../examples/basic.yml".to_string());
     let root_cfg = Config::new(&root_cfg_file_path)?;
     match root_cfg {
-        Config::Root { languages, .. } => {
+        Config::Root { ref languages, .. } => {
             // We use a custom i18n system to avoid having to inject locales into the root `index.html` file (I spent two hours on that...)
             // We just generate a page for each language/workflow combination
-            // We assume workflows are the same for all languages, so we can choose a random one
-            match languages.keys().collect::<Vec<&String>>().get(0) {
-                Some(key) => {
-                    let language_cfg_path = languages.get(&key.to_string()).unwrap(); // We selected a random key, it certainly should have an entry!
+            // We assume workflows are the same for all languages (`tribble check` will make sure of that), so we use the reference locale
+            match root_cfg.reference_locale() {
+                Some(locale) => {
+                    let language_cfg_path = match languages.get(locale) {
+                        Some(path) => path,
+                        None => {
+                            return Err(ParserError::NoSuchReferenceLocale {
+                                filename: root_cfg_file_path,
+                                locale: locale.to_string(),
+                            }
+                            .into())
+                        }
+                    };
                     let language_cfg = Config::new(language_cfg_path)?;
                     match language_cfg {
                         Config::Language { workflows, .. } => {
@@ -28,7 +36,7 @@ pub async fn get_build_paths() -> RenderFnResult<Vec<String>> {
                             let mut pages = Vec::new();
                             for workflow_name in workflows.keys() {
                                 for lang in languages.keys() {
-                                    pages.push(format!("{}/{}", lang, workflow_name));
+                                    pages.push(build_path(lang, workflow_name));
                                 }
                             }
                             // For each workflow, generate a separate page
@@ -51,7 +59,10 @@ pub async fn get_build_paths() -> RenderFnResult<Vec<String>> {
         }
         Config::Language { workflows, .. } => {
             // For each workflow, generate a separate page
-            Ok(workflows.keys().cloned().collect::<Vec<String>>())
+            Ok(workflows
+                .keys()
+                .map(|workflow_name| build_path("", workflow_name))
+                .collect::<Vec<String>>())
         }
     }
 }
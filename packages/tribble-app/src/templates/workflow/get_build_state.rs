@@ -4,14 +4,23 @@ use perseus::RenderFnResultWithCause;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    check::{check_defaults, check_template_refs},
     errors::ParserError,
-    parser::{Config, Workflow},
+    option_files::resolve_option_files,
+    parser::{Config, ErrorText, ResumeText, Workflow},
 };
 
 #[derive(Serialize, Deserialize)]
 pub struct WorkflowProps {
+    pub name: String,
+    /// The locale the workflow is in, which is empty if the app isn't translated.
+    pub locale: String,
     pub workflow: Workflow,
     pub input_err_msg: String,
+    pub resume: ResumeText,
+    pub errors: ErrorText,
+    /// A hash of the workflow, which saved progress is marked with so that progress from an older version of the workflow can be discarded.
+    pub version: String,
 }
 
 #[perseus::autoserde(build_state)]
@@ -26,14 +35,19 @@ pub async fn get_build_state(
         env::var("TRIBBLE_CONF").unwrap_or_else(|_| "../../../examples/basic.yml".to_string());
     let root_cfg = Config::new(&root_cfg_path)?;
     let input_err_msg;
+    let resume;
+    let errors;
+    // The file the workflow came from, for error messages
+    let filename;
     // This will be a different part of the path depending on whether or not we're using i18n
     let workflow_name;
+    let locale;
     // Get the workflows for the appropriate locale (if applicable)
     let workflows = match root_cfg {
-        Config::Root { languages } => {
+        Config::Root { languages, .. } => {
             let path_vec: Vec<&str> = path.split('/').collect();
             // These two parts are guaranteed by the `get_build_paths` code
-            let locale = path_vec[0];
+            locale = path_vec[0];
             workflow_name = path_vec[1];
             // We want the language file for the current locale
             let lang_cfg_path = match languages.get(locale) {
@@ -46,8 +60,14 @@ pub async fn get_build_state(
                 Config::Language {
                     workflows,
                     input_err_msg: input_err_msg_l,
+                    resume: resume_l,
+                    errors: errors_l,
+                    ..
                 } => {
                     input_err_msg = input_err_msg_l;
+                    resume = resume_l;
+                    errors = errors_l;
+                    filename = lang_cfg_path.to_string();
                     workflows
                 }
                 // A root file links to another root file (we only test one language in the build paths stage, so we may not have picked this up)
@@ -63,20 +83,61 @@ pub async fn get_build_state(
         Config::Language {
             workflows,
             input_err_msg: input_err_msg_l,
+            resume: resume_l,
+            errors: errors_l,
+            ..
         } => {
             workflow_name = path;
+            locale = "";
             input_err_msg = input_err_msg_l;
+            resume = resume_l;
+            errors = errors_l;
+            filename = root_cfg_path.clone();
             workflows
         }
     };
-    // Each workflow should match exactly to a page path (the pages are generated from the keys of the `workflows` map)
-    let workflow = match workflows.get(workflow_name) {
-        Some(workflow) => workflow,
-        None => unreachable!(),
+    // Each workflow should match exactly to a page path (the pages are generated from the keys of the `workflows` map), but those keys
+    // come from the reference locale, so a translation that's drifted might not have this workflow
+    let mut workflow = match workflows.get(workflow_name) {
+        Some(workflow) => workflow.clone(),
+        None => {
+            return Err(ParserError::MissingTranslatedWorkflow {
+                filename: root_cfg_path,
+                workflow: workflow_name.to_string(),
+            }
+            .into())
+        }
     };
 
+    // Options can come from data files, which we load now so the client gets the options themselves
+    resolve_option_files(&filename, &mut workflow)?;
+    // Report templates are parsed with the rest of the config, but we also need to make sure they'll actually have values to work with
+    check_template_refs(&filename, workflow_name, &workflow)?;
+    // Defaults are checked against their inputs' validation rules too, otherwise the user could get stuck with a default they didn't write
+    check_defaults(&filename, workflow_name, &workflow)?;
+
     Ok(WorkflowProps {
-        workflow: workflow.clone(),
+        name: workflow_name.to_string(),
+        locale: locale.to_string(),
+        version: workflow_version(&workflow),
+        workflow,
         input_err_msg,
+        resume,
+        errors,
     })
 }
+
+/// Hashes the given workflow (after its options have been loaded from any data files). This uses FNV-1a, because Rust's default hasher
+/// isn't guaranteed to give the same results between builds.
+fn workflow_version(workflow: &Workflow) -> String {
+    // Maps in a `serde_json::Value` are sorted, so the same workflow always gives the same text
+    let src = serde_json::to_value(workflow)
+        .map(|value| value.to_string())
+        .unwrap_or_default();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in src.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
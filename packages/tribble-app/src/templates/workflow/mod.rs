@@ -1,6 +1,8 @@
 mod get_build_paths;
 mod get_build_state;
-mod parse_md;
+pub mod parse_md;
+mod progress;
+mod submit;
 mod view;
 
 use perseus::{Html, SsrNode, Template};
@@ -2,6 +2,7 @@ use pulldown_cmark::{html, Options, Parser};
 
 /// Parses the given Markdown into an HTML string. This does not perform any santizing of the resulting HTML, so only parse trusted content through here! (This is designed to be used on
 /// user-given strings from their Tribble configurations, which they're then serving as their own websites, so security shouldn't be a problem here.)
+/// Templates that fill in what users have entered must be rendered with [`Template::render_md`](crate::template::Template::render_md) first.
 pub fn parse_md_to_html(markdown: &str) -> String {
     let mut opts = Options::empty();
     opts.insert(Options::ENABLE_STRIKETHROUGH);
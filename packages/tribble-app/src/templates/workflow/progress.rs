@@ -0,0 +1,193 @@
+use super::view::SectionResult;
+use crate::parser::{InputSectionElem, Section, SectionElem};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use web_sys::Storage;
+
+/// The progress a user has made through a workflow, which is saved in their browser so they can pick up where they left off if they leave
+/// the page. This is also what's put in links to a step.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SavedProgress {
+    /// The version of the workflow this progress was made in. If the workflow has changed since, the progress is thrown away, because it
+    /// might reference sections or inputs that don't exist anymore.
+    pub version: String,
+    pub history: Vec<SectionResult>,
+    pub history_pos: usize,
+    pub loc: String,
+    /// The values of inputs, as they're serialized for reports. These can only be turned back into typed values with the inputs they came
+    /// from.
+    pub form_values: HashMap<String, serde_json::Value>,
+}
+impl SavedProgress {
+    /// Checks that the position in the history is actually in it, and that it's where the user is. Anything that isn't will break the
+    /// workflow if it's restored, which can happen if it's been edited by hand.
+    pub fn is_valid(&self) -> bool {
+        self.history
+            .get(self.history_pos)
+            .is_some_and(|res| res.name == self.loc)
+    }
+    /// Removes the values of all the inputs in the given sections that match the given predicate, including the values of those inputs in
+    /// every entry of a group.
+    pub fn remove_values(
+        &mut self,
+        sections: &HashMap<String, Section>,
+        remove: impl Fn(&InputSectionElem) -> bool,
+    ) {
+        for elem in sections.values().flatten() {
+            match elem {
+                SectionElem::Input(input) if remove(input) => {
+                    self.form_values.remove(&input.id);
+                }
+                SectionElem::Group(group) => {
+                    if let Some(serde_json::Value::Array(entries)) =
+                        self.form_values.get_mut(&group.group)
+                    {
+                        for entry in entries.iter_mut() {
+                            if let serde_json::Value::Object(entry) = entry {
+                                for input in group.inputs.iter().filter(|input| remove(input)) {
+                                    entry.remove(&input.id);
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    /// Cuts this progress down to what a link to a step is allowed to hold: never the values of sensitive inputs in the given sections, and
+    /// no values at all if they aren't wanted. This is applied when a link is opened as well as when it's made, because links can be
+    /// written by hand.
+    pub fn restrict_shared(&mut self, sections: &HashMap<String, Section>, include_values: bool) {
+        if include_values {
+            self.remove_values(sections, InputSectionElem::is_sensitive);
+        } else {
+            self.form_values.clear();
+        }
+    }
+}
+
+/// Gets the key that progress through the given workflow in the given locale is saved under.
+pub fn storage_key(locale: &str, workflow: &str) -> String {
+    format!("tribble-progress:{}:{}", locale, workflow)
+}
+
+/// Gets the browser's local storage, if it's available (it can be disabled, or unavailable in private browsing). This must only be called in
+/// the browser.
+pub fn storage() -> Option<Storage> {
+    web_sys::window().unwrap().local_storage().ok().flatten()
+}
+
+/// Loads the progress saved under the given key, if there is any and it was made in the given version of the workflow. Progress from any
+/// other version is deleted.
+pub fn load_progress(key: &str, version: &str) -> Option<SavedProgress> {
+    let storage = storage()?;
+    let saved = storage.get_item(key).ok().flatten()?;
+    match serde_json::from_str::<SavedProgress>(&saved) {
+        Ok(progress) if progress.version == version && progress.is_valid() => Some(progress),
+        // Either the workflow has changed or the data is corrupted, and we can't use it either way
+        _ => {
+            let _ = storage.remove_item(key);
+            None
+        }
+    }
+}
+
+/// Saves the given progress under the given key. If the browser won't let us, the user just won't be able to resume later.
+pub fn save_progress(key: &str, progress: &SavedProgress) {
+    if let (Some(storage), Ok(saved)) = (storage(), serde_json::to_string(progress)) {
+        let _ = storage.set_item(key, &saved);
+    }
+}
+
+/// Encodes the given progress into the fragment of a link to the step it's at (e.g. `#state=eyJ2ZXJzaW9uIjoi...`).
+pub fn encode_permalink(progress: &SavedProgress) -> String {
+    let json = serde_json::to_string(progress).unwrap_or_default();
+    format!(
+        "#state={}",
+        base64::encode_config(json, base64::URL_SAFE_NO_PAD)
+    )
+}
+
+/// Decodes the progress from the given URL fragment (including the `#`), if it's a link to a step made in the given version of the workflow.
+pub fn decode_permalink(fragment: &str, version: &str) -> Option<SavedProgress> {
+    let encoded = fragment.strip_prefix("#state=")?;
+    let json = base64::decode_config(encoded, base64::URL_SAFE_NO_PAD).ok()?;
+    serde_json::from_slice::<SavedProgress>(&json)
+        .ok()
+        .filter(|progress| progress.version == version && progress.is_valid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sections() -> HashMap<String, Section> {
+        serde_yaml::from_str(
+            r#"
+Start:
+  - { id: "name", label: "Name", type: "text" }
+  - { id: "token", label: "Token", type: "password" }
+  - { id: "email", label: "Email", type: "email", sensitive: true }
+  - group: "steps"
+    label: "Steps"
+    inputs:
+      - { id: "action", label: "Action", type: "text" }
+      - { id: "secret", label: "Secret", type: "password" }
+"#,
+        )
+        .unwrap()
+    }
+    fn progress() -> SavedProgress {
+        serde_json::from_value(json!({
+            "version": "1",
+            "history": [ { "name": "Start", "tags": [] } ],
+            "history_pos": 0,
+            "loc": "Start",
+            "form_values": {
+                "name": "Alice",
+                "token": "hunter2",
+                "email": "alice@example.com",
+                "steps": [ { "action": "Clicked", "secret": "hunter2" } ]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn shared_progress_leaves_out_sensitive_values() {
+        let mut shared = progress();
+        shared.restrict_shared(&sections(), true);
+        assert_eq!(
+            serde_json::to_value(&shared.form_values).unwrap(),
+            json!({
+                "name": "Alice",
+                "steps": [ { "action": "Clicked" } ]
+            })
+        );
+    }
+    #[test]
+    fn shared_progress_can_leave_out_every_value() {
+        let mut shared = progress();
+        shared.restrict_shared(&sections(), false);
+        assert!(shared.form_values.is_empty());
+        // Where the user is doesn't change
+        assert_eq!(shared.loc, "Start");
+        assert!(shared.is_valid());
+    }
+    #[test]
+    fn inconsistent_progress_is_invalid() {
+        assert!(progress().is_valid());
+        let mut saved = progress();
+        saved.history_pos = 1;
+        assert!(!saved.is_valid());
+        let mut saved = progress();
+        saved.loc = "Elsewhere".to_string();
+        assert!(!saved.is_valid());
+        let mut saved = progress();
+        saved.history.clear();
+        saved.history_pos = 0;
+        assert!(!saved.is_valid());
+    }
+}
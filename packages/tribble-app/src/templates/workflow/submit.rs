@@ -0,0 +1,139 @@
+use crate::form::FormValue;
+use serde::Serialize;
+use std::collections::HashMap;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Request, RequestInit, Response};
+
+/// The data sent to the receiver of a submitted report.
+#[derive(Serialize)]
+pub struct ReportPayload {
+    /// The name of the workflow the report came from.
+    pub workflow: String,
+    /// The name of the endpoint the report came from.
+    pub endpoint: String,
+    /// The fully interpolated report text (without the internal tags data, which is provided separately).
+    pub text: String,
+    /// All the tags the user accumulated on their way to this endpoint.
+    pub tags: Vec<String>,
+    /// The values of every input the user filled out (lists for inputs that allow multiple options, booleans for checkboxes, etc.).
+    pub form_values: HashMap<String, FormValue>,
+}
+
+/// The state of submitting a report.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SubmitState {
+    /// The user hasn't tried to submit the report yet.
+    Idle,
+    /// The request is in flight.
+    Loading,
+    /// The receiver accepted the report.
+    Success,
+    /// The request failed. The user doesn't see this error (they see the configured error message instead), but it's logged to the console.
+    Error(String),
+}
+
+/// Sends the given report as JSON to the given URL with a `POST` request.
+pub async fn submit_report(
+    url: &str,
+    headers: &HashMap<String, String>,
+    payload: &ReportPayload,
+) -> Result<(), String> {
+    let body = serde_json::to_string(payload).map_err(|err| err.to_string())?;
+    let opts = RequestInit::new();
+    opts.set_method("POST");
+    opts.set_body(&JsValue::from_str(&body));
+    let request = Request::new_with_str_and_init(url, &opts).map_err(fmt_js_err)?;
+    let req_headers = request.headers();
+    req_headers
+        .set("Content-Type", "application/json")
+        .map_err(fmt_js_err)?;
+    for (name, value) in headers {
+        req_headers.set(name, value).map_err(fmt_js_err)?;
+    }
+
+    let window = web_sys::window().unwrap();
+    let res = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(fmt_js_err)?;
+    let res: Response = res.unchecked_into();
+    if res.ok() {
+        Ok(())
+    } else {
+        Err(format!(
+            "report receiver responded with status code {}",
+            res.status()
+        ))
+    }
+}
+
+/// Formats an error from JS as best we can.
+fn fmt_js_err(err: JsValue) -> String {
+    err.as_string().unwrap_or_else(|| format!("{:?}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::form::GroupEntry;
+    use serde_json::json;
+
+    #[test]
+    fn payload_is_serialized_with_typed_values() {
+        let mut form_values = HashMap::new();
+        form_values.insert(
+            "description".to_string(),
+            FormValue::Single("It broke".to_string()),
+        );
+        form_values.insert(
+            "areas".to_string(),
+            FormValue::List(vec!["Frontend, mostly".to_string(), "Backend".to_string()]),
+        );
+        form_values.insert("bool".to_string(), FormValue::Boolean(false));
+        form_values.insert("version".to_string(), FormValue::Number(Some(2.5)));
+        form_values.insert("count".to_string(), FormValue::Number(None));
+        form_values.insert(
+            "logs".to_string(),
+            FormValue::Code {
+                text: "panicked at 'oops'".to_string(),
+                language: Some("console".to_string()),
+                max_lines: Some(30),
+                truncated_msg: "Show all".to_string(),
+            },
+        );
+        form_values.insert(
+            "steps".to_string(),
+            FormValue::Group(vec![GroupEntry(vec![(
+                "action".to_string(),
+                FormValue::Single("Clicked".to_string()),
+            )])]),
+        );
+        let payload = ReportPayload {
+            workflow: "test".to_string(),
+            endpoint: "Bug".to_string(),
+            text: "Report".to_string(),
+            tags: vec!["C:bug".to_string()],
+            form_values,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            json!({
+                "workflow": "test",
+                "endpoint": "Bug",
+                "text": "Report",
+                "tags": [ "C:bug" ],
+                "form_values": {
+                    "description": "It broke",
+                    "areas": [ "Frontend, mostly", "Backend" ],
+                    "bool": false,
+                    "version": 2.5,
+                    "count": null,
+                    // How the code is shown is only for reports
+                    "logs": { "text": "panicked at 'oops'", "language": "console" },
+                    "steps": [ { "action": "Clicked" } ]
+                }
+            })
+        );
+    }
+}
@@ -1,16 +1,29 @@
 use super::get_build_state::WorkflowProps;
 use super::parse_md::parse_md_to_html;
+use super::progress::{
+    decode_permalink, encode_permalink, load_progress, save_progress, storage_key, SavedProgress,
+};
+use super::submit::{submit_report, ReportPayload, SubmitState};
+use crate::condition::Condition;
+use crate::diagnostics::fmt_key;
+use crate::form::{FormValue, GroupEntry};
 use crate::parser::{
-    Endpoint, Input, InputSectionElem, InputType, Section, SectionElem, SelectOption,
+    Endpoint, GiteaDest, GithubDest, GitlabDest, Input, InputGroup, InputSectionElem, InputType,
+    Prefill, ReportSubmit, ResumeText, Section, SectionElem, SelectOption, ShareLink,
 };
+use crate::report::{fit_url, gitea_url, github_url, gitlab_url, DestLink};
 use crate::svg;
+use crate::template::{add_fields, Template};
+use crate::validation::{matching_tags, validate};
 #[cfg(not(debug_assertions))]
 use js_sys::Function;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 use sycamore::context::{use_context, ContextProvider, ContextProviderProps};
 use sycamore::prelude::*;
-use wasm_bindgen::JsCast;
-use web_sys::HtmlOptionElement;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{HtmlOptionElement, PopStateEvent, UrlSearchParams};
 
 /// The context of a workflow (these need to be accessed by multiple different parts of the workflow).
 #[derive(Clone, Debug)]
@@ -25,39 +38,314 @@ struct WorkflowCtx {
     history_pos: Signal<usize>,
     /// The current location (either a section or an endpoint).
     loc: Signal<String>,
-    /// The values types in different inputs, which can be later referenced for interpolation. Selects with multiple values submit their values as a comma-delimited list.
-    form_values: Signal<HashMap<String, Signal<String>>>,
+    /// The values typed in different inputs, which can be later referenced for interpolation and in conditions. These are typed by the kind
+    /// of input they came from, so inputs that allow multiple options have lists of values.
+    form_values: Signal<HashMap<String, Signal<FormValue>>>,
+    /// All the sections in the workflow, which we need to follow redirects before the user gets to a section.
+    sections: Rc<HashMap<String, Section>>,
+    /// Progress the user saved the last time they were here, which they haven't yet decided whether to pick up or throw away. Nothing is
+    /// saved while this is waiting, so it can't be overwritten.
+    saved: Signal<Option<SavedProgress>>,
 }
 impl WorkflowCtx {
-    fn new(index_loc: String) -> Self {
-        Self {
+    fn new(index_loc: String, sections: HashMap<String, Section>) -> Self {
+        let ctx = Self {
             loc: Signal::new(index_loc.clone()),
             // The history should start on the first section
             history: Signal::new(vec![SectionResult {
-                name: index_loc,
+                name: index_loc.clone(),
                 tags: Vec::new(),
             }]),
             history_pos: Signal::new(0), // There's no history at this point, so this is safe
             form_values: Signal::default(),
+            sections: Rc::new(sections),
+            saved: Signal::new(None),
+        };
+        // Even the index can redirect straight away (though there won't be any tags or form values yet, and there's nowhere to put any
+        // tags the redirect would accumulate)
+        let (loc, _) = ctx.resolve_redirects(index_loc, &[]);
+        ctx.history.set(vec![SectionResult {
+            name: loc.clone(),
+            tags: Vec::new(),
+        }]);
+        ctx.loc.set(loc);
+
+        ctx
+    }
+    /// Follows any automatic redirects from the given location, returning where the user should actually end up and the tags the redirects
+    /// accumulated along the way. The given tags should be everything the user has accumulated so far. Sections that redirect never appear
+    /// in the history, so going back will skip straight over them.
+    fn resolve_redirects(&self, mut loc: String, tags: &[String]) -> (String, Vec<String>) {
+        let mut all_tags = tags.to_vec();
+        let mut new_tags = Vec::new();
+        let form_values = self.form_values.get();
+        // If we ever get back to somewhere we've already been redirected from, we'd loop forever, so we stop there
+        let mut visited = HashSet::new();
+        while visited.insert(loc.clone()) {
+            // Endpoints can't redirect
+            let section = match self.sections.get(&loc) {
+                Some(section) => section,
+                None => break,
+            };
+            // The first redirect whose condition is true wins
+            let redirect = section.iter().find_map(|elem| match elem {
+                SectionElem::Redirect {
+                    redirect,
+                    when,
+                    tags,
+                } => {
+                    let follow = match when {
+                        Some(when) => when.eval(&all_tags, |id| {
+                            form_values.get(id).map(|value| (*value.get()).clone())
+                        }),
+                        None => true,
+                    };
+                    if follow {
+                        Some((redirect.clone(), tags.clone()))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            });
+            match redirect {
+                Some((target, tags)) => {
+                    all_tags.extend(tags.iter().cloned());
+                    new_tags.extend(tags);
+                    loc = target;
+                }
+                None => break,
+            }
         }
+
+        (loc, new_tags)
+    }
+    /// Fills in inputs and picks the section to start at from the query parameters of the page's URL (e.g. `?version=1.4.2&start=Report%20Bug`),
+    /// but only for the inputs and sections the workflow allows to be prefilled. This must only be called in the browser.
+    fn prefill(&self, prefill: &Prefill) {
+        let search = web_sys::window()
+            .unwrap()
+            .location()
+            .search()
+            .unwrap_or_default();
+        let params = match UrlSearchParams::new_with_str(&search) {
+            Ok(params) => params,
+            // A malformed query string just means there's nothing to prefill
+            Err(_) => return,
+        };
+
+        let mut form_values = (*self.form_values.get()).clone();
+        for id in &prefill.inputs {
+            let input = self
+                .sections
+                .values()
+                .flatten()
+                .find_map(|elem| match elem {
+                    SectionElem::Input(input) if &input.id == id => Some(input),
+                    _ => None,
+                });
+            let input = match input {
+                Some(input) => input,
+                // `tribble check` will have caught this
+                None => continue,
+            };
+            let mut values: Vec<String> = params
+                .get_all(id)
+                .iter()
+                .filter_map(|value| value.as_string())
+                .collect();
+            // Inputs with options can only be filled in with those options
+            if let Some(options) = input.input.options() {
+                values.retain(|value| options.iter().any(|opt| opt.text() == value.as_str()));
+            }
+            let value = match values.first() {
+                Some(_) if input.input.is_multiple() => FormValue::List(values),
+                Some(value) => FormValue::from_text(&input.input, value),
+                None => continue,
+            };
+            form_values.insert(id.to_string(), Signal::new(value));
+        }
+        self.form_values.set(form_values);
+
+        if let Some(start) = params.get("start") {
+            if prefill.sections.contains(&start) {
+                let (loc, _) = self.resolve_redirects(start, &[]);
+                self.history.set(vec![SectionResult {
+                    name: loc.clone(),
+                    tags: Vec::new(),
+                }]);
+                self.loc.set(loc);
+            }
+        }
+    }
+    /// Takes a snapshot of the user's progress so it can be saved in the browser, leaving out the values of sensitive inputs (which shouldn't
+    /// be stored anywhere). If this is called in a reactive scope, it will be re-run whenever any of that progress changes (including the
+    /// values of inputs).
+    fn to_saved(&self, version: &str) -> SavedProgress {
+        let mut progress = SavedProgress {
+            version: version.to_string(),
+            history: (*self.history.get()).clone(),
+            history_pos: *self.history_pos.get(),
+            loc: (*self.loc.get()).clone(),
+            form_values: self
+                .form_values
+                .get()
+                .iter()
+                .filter_map(|(id, value)| {
+                    Some((id.to_string(), serde_json::to_value(&*value.get()).ok()?))
+                })
+                .collect(),
+        };
+        progress.remove_values(&self.sections, InputSectionElem::is_sensitive);
+
+        progress
+    }
+    /// Takes a snapshot of the user's progress to put in a link to the step they're on, leaving out the values of sensitive inputs (or every
+    /// value, if they aren't wanted).
+    fn to_shared(&self, version: &str, include_values: bool) -> SavedProgress {
+        let mut progress = self.to_saved(version);
+        progress.restrict_shared(&self.sections, include_values);
+
+        progress
+    }
+    /// Turns the values in the given saved progress back into typed values, using the inputs (and groups) they came from. Any values that
+    /// don't fit their inputs are left out.
+    fn saved_values(&self, saved: &SavedProgress) -> HashMap<String, FormValue> {
+        let mut values = HashMap::new();
+        for elem in self.sections.values().flatten() {
+            match elem {
+                SectionElem::Input(input) => {
+                    if let Some(value) = saved
+                        .form_values
+                        .get(&input.id)
+                        .and_then(|value| FormValue::from_saved(&input.input, value))
+                    {
+                        values.insert(input.id.to_string(), value);
+                    }
+                }
+                SectionElem::Group(group) => {
+                    if let Some(serde_json::Value::Array(entries)) =
+                        saved.form_values.get(&group.group)
+                    {
+                        let entries = entries
+                            .iter()
+                            .map(|entry| {
+                                GroupEntry(
+                                    group
+                                        .inputs
+                                        .iter()
+                                        .map(|input| {
+                                            let value = entry
+                                                .get(&input.id)
+                                                .and_then(|value| {
+                                                    FormValue::from_saved(&input.input, value)
+                                                })
+                                                .unwrap_or_else(|| FormValue::from_input(input));
+                                            (input.id.to_string(), value)
+                                        })
+                                        .collect(),
+                                )
+                            })
+                            .collect();
+                        values.insert(group.group.to_string(), FormValue::Group(entries));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        values
+    }
+    /// Checks whether or not the given saved progress is worth offering to resume, which it is if the user got past the first section or
+    /// changed any input from how it started out.
+    fn is_worth_resuming(&self, saved: &SavedProgress) -> bool {
+        if saved.history.len() > 1 {
+            return true;
+        }
+        let values = self.saved_values(saved);
+        self.sections.values().flatten().any(|elem| {
+            let (id, initial) = match elem {
+                SectionElem::Input(input) => (&input.id, FormValue::from_input(input)),
+                SectionElem::Group(group) => {
+                    let entry = GroupEntry(
+                        group
+                            .inputs
+                            .iter()
+                            .map(|input| (input.id.to_string(), FormValue::from_input(input)))
+                            .collect(),
+                    );
+                    (&group.group, FormValue::Group(vec![entry; group.min]))
+                }
+                _ => return false,
+            };
+            values.get(id).is_some_and(|value| *value != initial)
+        })
+    }
+    /// Picks up the given saved progress where the user left off, replacing everything they've done since they got here.
+    fn restore(&self, saved: &SavedProgress) {
+        let form_values = self
+            .saved_values(saved)
+            .into_iter()
+            .map(|(id, value)| (id, Signal::new(value)))
+            .collect();
+        // The values have to be in place before the section that uses them is rendered
+        self.form_values.set(form_values);
+        self.history.set(saved.history.clone());
+        self.history_pos.set(saved.history_pos);
+        self.loc.set(saved.loc.clone());
+        replace_browser_entry(saved.history_pos);
+    }
+    /// Moves the user to the given position in their history when they use the browser's back or forward buttons, if it's still there.
+    fn go_to_pos(&self, pos: usize) {
+        let history = self.history.get();
+        // A progression made since the browser's entry for this position was made may have cut the history short
+        let pos = pos.min(history.len() - 1);
+        self.history_pos.set(pos);
+        self.loc.set(history[pos].name.clone());
+    }
+    /// Evaluates the given condition against the tags accumulated in the sections before the current one and the current values of all
+    /// inputs. If this is called in a reactive scope, it will be re-run whenever any of those change.
+    fn eval(&self, cond: &Condition) -> bool {
+        self.eval_in_entry(cond, &[])
+    }
+    /// Evaluates the given condition like [`Self::eval`], but for an input in an entry of a group, whose condition can also reference the
+    /// other inputs in that entry (the given IDs and values, which take precedence over inputs outside the group).
+    fn eval_in_entry(&self, cond: &Condition, entry: &[(String, Signal<FormValue>)]) -> bool {
+        let history = self.history.get();
+        let history_pos = *self.history_pos.get();
+        let tags: Vec<String> = history
+            .iter()
+            .take(history_pos)
+            .flat_map(|res| res.tags.iter().cloned())
+            .collect();
+        let form_values = self.form_values.get();
+        cond.eval(&tags, |id| {
+            entry
+                .iter()
+                .find(|(input_id, _)| input_id == id)
+                .map(|(_, value)| value)
+                .or_else(|| form_values.get(id))
+                .map(|value| (*value.get()).clone())
+        })
     }
 }
 
 /// The results from a section. A vector of these can be used to track history.
-#[derive(Clone, Debug)]
-struct SectionResult {
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SectionResult {
     tags: Vec<String>,
-    name: String,
+    pub(super) name: String,
 }
 
 #[perseus::template(Workflow)]
 #[component(Workflow<G>)]
 pub fn workflow(props: WorkflowProps) -> View<G> {
     let index_loc = props.workflow.index.clone();
-    // If we're in the browser, immediately tell it that we want to prompt the user before they leave the page
-    // we'll only actually do this if we're in dev mode
+    let sections = props.workflow.sections.clone();
+    // If we're in the browser and we can't save the user's progress, immediately tell it that we want to prompt the user before they leave
+    // the page (we'll only actually do this if we're in dev mode)
     #[cfg(not(debug_assertions))]
-    if G::IS_BROWSER {
+    if G::IS_BROWSER && super::progress::storage().is_none() {
         let window = web_sys::window().unwrap();
         window.set_onbeforeunload(Some(&Function::new_with_args(
             "ev",
@@ -65,10 +353,85 @@ pub fn workflow(props: WorkflowProps) -> View<G> {
         )));
     }
 
+    let ctx = WorkflowCtx::new(index_loc, sections);
+    // Links to the workflow can fill in inputs and pick the section to start at (there are no query parameters on the server)
+    if G::IS_BROWSER {
+        ctx.prefill(&props.workflow.prefill);
+        // Every entry in the browser's history that we make holds a position in the user's history, starting with the one they arrived on
+        replace_browser_entry(*ctx.history_pos.get());
+        // Progress is saved separately for each workflow in each locale, and we'll offer to pick it up if there's something worth picking up
+        let key = storage_key(&props.locale, &props.name);
+        let window = web_sys::window().unwrap();
+        let fragment = window.location().hash().unwrap_or_default();
+        if fragment.starts_with("#state=") {
+            // A link to a step takes priority over anything the user saved themselves (which it'll be saved over), but only if this workflow
+            // allows them, and, because they can be written by hand, only with what a link we made could hold
+            match (&props.workflow.share, decode_permalink(&fragment, &props.version)) {
+                (Some(share), Some(mut shared)) => {
+                    shared.restrict_shared(&ctx.sections, share.include_values);
+                    ctx.restore(&shared);
+                }
+                (None, _) => web_sys::console::warn_1(
+                    &"ignoring link to a step, because this workflow doesn't allow them".into(),
+                ),
+                (Some(_), None) => web_sys::console::warn_1(
+                    &"ignoring link to a step, because it's invalid or from an older version of this workflow".into(),
+                ),
+            }
+            // The link shouldn't be applied again if the user refreshes (that would throw away everything they've done since)
+            let location = window.location();
+            let url = format!(
+                "{}{}",
+                location.pathname().unwrap_or_default(),
+                location.search().unwrap_or_default()
+            );
+            if let Ok(history) = window.history() {
+                let pos = JsValue::from_f64(*ctx.history_pos.get() as f64);
+                let _ = history.replace_state_with_url(&pos, "", Some(&url));
+            }
+        } else if let Some(saved) = load_progress(&key, &props.version) {
+            if ctx.is_worth_resuming(&saved) {
+                ctx.saved.set(Some(saved));
+            }
+        }
+        // The browser's back and forward buttons should move through sections rather than leaving the workflow
+        let popstate_handler = Closure::wrap(Box::new(cloned!(ctx => move |ev: PopStateEvent| {
+            // Anything else (like a changed fragment) isn't ours to handle
+            if let Some(pos) = ev.state().as_f64() {
+                // The router would otherwise treat this as navigating to a new page and throw away the user's progress
+                ev.stop_immediate_propagation();
+                ctx.go_to_pos(pos as usize);
+            }
+        })) as Box<dyn Fn(PopStateEvent)>);
+        // We listen in the capturing phase so we get the event before the router does
+        let _ = window.add_event_listener_with_callback_and_bool(
+            "popstate",
+            popstate_handler.as_ref().unchecked_ref(),
+            true,
+        );
+        on_cleanup(move || {
+            let _ = web_sys::window()
+                .unwrap()
+                .remove_event_listener_with_callback_and_bool(
+                    "popstate",
+                    popstate_handler.as_ref().unchecked_ref(),
+                    true,
+                );
+        });
+        let version = props.version.clone();
+        create_effect(cloned!(ctx => move || {
+            // We don't want to overwrite the saved progress before the user's decided what to do with it
+            if ctx.saved.get().is_some() {
+                return;
+            }
+            save_progress(&key, &ctx.to_saved(&version));
+        }));
+    }
+
     view! {
         // We pass tags around with context to avoid throwing `Signal`s over the place
         ContextProvider(ContextProviderProps {
-            value: WorkflowCtx::new(index_loc),
+            value: ctx,
             children: || view! {
                 WorkflowInner(props)
             }
@@ -79,24 +442,51 @@ pub fn workflow(props: WorkflowProps) -> View<G> {
 #[component(WorkflowInner<G>)]
 pub fn workflow_inner(
     WorkflowProps {
+        name: workflow_name,
+        locale,
         workflow,
         input_err_msg,
+        resume,
+        errors,
+        version,
     }: WorkflowProps,
 ) -> View<G> {
     // This will be the name of a section, or, if it's prefixed with `endpoint:`, an endpoint
     let loc = use_context::<WorkflowCtx>().loc;
     // This will either store an endpoint or a section, fully rendered
-    let page: ReadSignal<View<G>> = create_memo(cloned!(workflow, loc => move || {
+    let page: ReadSignal<View<G>> = create_memo(cloned!(workflow, workflow_name, loc => move || {
         let loc = &*loc.get();
         if loc.starts_with("endpoint:") {
             let loc = loc.strip_prefix("endpoint:").unwrap();
             let endpoint_props = match workflow.endpoints.get(loc) {
                 Some(props) => props,
-                None => todo!("handle errors in pages (no such endpoint)")
+                None => return view! {
+                    MissingTarget(MissingTargetProps {
+                        msg: errors.missing_endpoint.replace("%s", loc),
+                        back_text: errors.back_text.clone(),
+                        sources: link_sources(&workflow_name, &workflow, &format!("endpoint:{}", loc)),
+                    })
+                }
             };
             match endpoint_props {
-                Endpoint::Report { preamble, text, dest_text, dest_url } => view! {
-                    RenderReportEndpoint(RenderReportEndpointProps { preamble: preamble.to_string(), text: text.to_string(), dest_text: dest_text.to_string(), dest_url: dest_url.to_string() })
+                Endpoint::Report { preamble, text, dest_text, dest_url, max_url_len, copied_msg, copy_failed_msg, github, gitlab, gitea, submit } => view! {
+                    RenderReportEndpoint(RenderReportEndpointProps {
+                        preamble: preamble.clone(),
+                        text: text.clone(),
+                        dest_text: dest_text.to_string(),
+                        dest_url: dest_url.clone(),
+                        max_url_len: *max_url_len,
+                        copied_msg: copied_msg.to_string(),
+                        copy_failed_msg: copy_failed_msg.to_string(),
+                        github: github.clone(),
+                        gitlab: gitlab.clone(),
+                        gitea: gitea.clone(),
+                        submit: submit.clone(),
+                        fields: workflow.fields.clone(),
+                        locale: locale.clone(),
+                        workflow_name: workflow_name.clone(),
+                        endpoint_name: loc.to_string(),
+                    })
                 },
                 Endpoint::Instructional(text) => {
                     let text = parse_md_to_html(text);
@@ -108,7 +498,13 @@ pub fn workflow_inner(
         } else {
             let section_props = match workflow.sections.get(loc) {
                 Some(props) => RenderSectionProps { section: props.clone(), input_err_msg: input_err_msg.clone(), name: loc.to_string() },
-                None => todo!("handle errors in pages (no such section)")
+                None => return view! {
+                    MissingTarget(MissingTargetProps {
+                        msg: errors.missing_section.replace("%s", loc),
+                        back_text: errors.back_text.clone(),
+                        sources: link_sources(&workflow_name, &workflow, loc),
+                    })
+                }
             };
             view! {
                 RenderSection(section_props)
@@ -116,11 +512,20 @@ pub fn workflow_inner(
         }
     }));
 
+    // Only some workflows want a button for copying links to steps
+    let share_button = match workflow.share.clone() {
+        Some(share) => view! {
+            ShareButton(ShareButtonProps { share, version })
+        },
+        None => View::empty(),
+    };
+
     view! {
         // We set the caret color at the top-level (changes the outlines of form inputs, cursor color, etc.)
         div(class = "flex justify-center w-full min-h-full py-2 xs:py-6 sm:py-8 md:py-16") {
             // TODO Top margins
             div(class = "section-container xs:shadow-md dark:xs:shadow-lg xs:rounded-lg text-center flex-col md:w-[48rem] m-auto", id = "section-content") {
+                ResumePrompt(resume)
                 HistoryBreadcrumbs()
                 // We want to alert screenreaders that this entire section can be swapped out for new content
                 div(class = "w-full flex flex-col justify-center") {
@@ -128,11 +533,205 @@ pub fn workflow_inner(
                         (*page.get())
                     }
                 }
+                (share_button)
             }
         }
     }
 }
 
+/// Renders a prompt asking the user whether they want to pick up where they left off the last time they were here, if they have any saved
+/// progress they haven't decided about yet.
+#[component(ResumePrompt<G>)]
+fn resume_prompt(
+    ResumeText {
+        msg,
+        resume_text,
+        restart_text,
+    }: ResumeText,
+) -> View<G> {
+    let ctx = use_context::<WorkflowCtx>();
+    let saved = ctx.saved.clone();
+    let has_saved = create_memo(move || saved.get().is_some());
+    let resume_handler = cloned!(ctx => move |_| {
+        if let Some(saved) = &*ctx.saved.get() {
+            ctx.restore(saved);
+        }
+        ctx.saved.set(None);
+    });
+    // The progress the user has made since they got here will be saved over the old progress as soon as this is gone
+    let restart_handler = cloned!(ctx => move |_| ctx.saved.set(None));
+
+    view! {
+        (if *has_saved.get() {
+            let msg = msg.clone();
+            let resume_text = resume_text.clone();
+            let restart_text = restart_text.clone();
+            let resume_handler = resume_handler.clone();
+            let restart_handler = restart_handler.clone();
+            view! {
+                div(class = "resume-prompt", role = "status") {
+                    p(class = "mb-2") { (msg) }
+                    div(class = "flex justify-center") {
+                        button(on:click = resume_handler, class = "resume-button") { (resume_text) }
+                        button(on:click = restart_handler, class = "resume-button") { (restart_text) }
+                    }
+                }
+            }
+        } else {
+            View::empty()
+        })
+    }
+}
+
+struct MissingTargetProps {
+    /// The message explaining what's missing.
+    msg: String,
+    /// The text of the button for going back to the previous section, which is only shown if there is one.
+    back_text: String,
+    /// The places in the configuration that link to whatever's missing, which are only shown in development.
+    sources: Vec<String>,
+}
+
+/// Renders an error for a section or endpoint that doesn't exist, with a button to go back to the previous section (if there was one).
+#[component(MissingTarget<G>)]
+fn missing_target(
+    MissingTargetProps {
+        msg,
+        back_text,
+        sources,
+    }: MissingTargetProps,
+) -> View<G> {
+    let ctx = use_context::<WorkflowCtx>();
+    let history_pos = *ctx.history_pos.get();
+    let back_button = if history_pos > 0 {
+        let back_handler = cloned!(ctx => move |_| {
+            // Whatever sent the user here is broken, so there's no point keeping it in the history
+            let mut history = (*ctx.history.get()).clone();
+            history.truncate(history_pos);
+            ctx.history.set(history);
+            ctx.go_to_pos(history_pos - 1);
+            replace_browser_entry(history_pos - 1);
+        });
+        view! {
+            button(on:click = back_handler, class = "back-button") { (back_text) }
+        }
+    } else {
+        View::empty()
+    };
+    // In development, we can point the maintainer to the problem (`tribble check` would have caught this too)
+    let hint = if cfg!(debug_assertions) && !sources.is_empty() {
+        let hint = format!(
+            "Linked to from {} (run `tribble check` to find problems like this).",
+            sources.join(", ")
+        );
+        view! {
+            p(class = "text-sm text-neutral-500 dark:text-neutral-400 mb-2") { (hint) }
+        }
+    } else {
+        View::empty()
+    };
+
+    view! {
+        div(role = "alert") {
+            p(class = "text-red-600 dark:text-red-400 mb-2") { (msg) }
+            (hint)
+        }
+        (back_button)
+    }
+}
+
+/// Gets the paths in the configuration of everything in the given workflow that links to the given section or endpoint (which should be
+/// prefixed with `endpoint:` if it's an endpoint).
+fn link_sources(
+    workflow_name: &str,
+    workflow: &crate::parser::Workflow,
+    target: &str,
+) -> Vec<String> {
+    let workflow_path = format!("workflows.{}", fmt_key(workflow_name));
+    let mut sources = Vec::new();
+    if workflow.index == target {
+        sources.push(format!("{}.index", workflow_path));
+    }
+    let mut sections: Vec<(&String, &Section)> = workflow.sections.iter().collect();
+    sections.sort_by_key(|(name, _)| *name);
+    for (section_name, section) in sections {
+        for (i, elem) in section.iter().enumerate() {
+            let field = match elem {
+                SectionElem::Progression { link, .. } if link == target => "link",
+                SectionElem::Redirect { redirect, .. } if redirect == target => "redirect",
+                _ => continue,
+            };
+            sources.push(format!(
+                "{}.sections.{}[{}].{}",
+                workflow_path,
+                fmt_key(section_name),
+                i,
+                field
+            ));
+        }
+    }
+
+    sources
+}
+
+struct ShareButtonProps {
+    share: ShareLink,
+    version: String,
+}
+
+/// Renders a button that copies a link to the step the user is on, which will rebuild their progress for whoever opens it.
+#[component(ShareButton<G>)]
+fn share_button(
+    ShareButtonProps {
+        share:
+            ShareLink {
+                text,
+                copied_text,
+                copy_failed_text,
+                include_values,
+            },
+        version,
+    }: ShareButtonProps,
+) -> View<G> {
+    let ctx = use_context::<WorkflowCtx>();
+    let copy_state = Signal::new(CopyState::Idle);
+    // A link that's been copied is only for the step it was copied on
+    create_effect(cloned!(ctx, copy_state => move || {
+        ctx.loc.get();
+        copy_state.set(CopyState::Idle);
+    }));
+    let click_handler = cloned!(ctx, copy_state => move |_| {
+        let href = web_sys::window().unwrap().location().href().unwrap_or_default();
+        // Any fragment that's already there is replaced
+        let base = href.split('#').next().unwrap_or_default();
+        let link = format!("{}{}", base, encode_permalink(&untrack(|| ctx.to_shared(&version, include_values))));
+        copy_to_clipboard(link, copy_state.clone());
+    });
+    // If we couldn't copy the link, we'll show it so the user can copy it themselves
+    let copy_failed = create_memo(cloned!(copy_state => move || match &*copy_state.get() {
+        CopyState::Failed(link) => {
+            let link = link.clone();
+            let copy_failed_text = copy_failed_text.clone();
+            view! {
+                p(class = "text-sm mt-2", role = "alert") { (copy_failed_text) }
+                input(type = "text", readonly = true, value = link, class = "copy-fallback") {}
+            }
+        }
+        _ => View::empty(),
+    }));
+
+    view! {
+        button(
+            on:click = click_handler,
+            class = "share-button",
+            aria-live = "polite"
+        ) {
+            (if *copy_state.get() == CopyState::Copied { copied_text.clone() } else { text.clone() })
+        }
+        ((*copy_failed.get()).clone())
+    }
+}
+
 struct RenderSectionProps {
     section: Section,
     input_err_msg: String,
@@ -150,9 +749,14 @@ fn render_section(
 ) -> View<G> {
     // We keep a local map of form values that we'll add to the global one on a progression (otherwise we're doing unecessary context reads)
     // We make this reactive at the value level, because each value will be updating (whereas the global one has values set, because they're submitted after the section is moved on from)
-    // This also stores the input's properties and a reactive boolean that will be `true` if an error message needs to be shown
+    // This also stores the input's properties and the error message that needs to be shown for it (if any)
     #[allow(clippy::type_complexity)]
-    let form_values: Signal<HashMap<String, (Signal<String>, InputSectionElem, Signal<bool>)>> =
+    let form_values: Signal<
+        HashMap<String, (Signal<FormValue>, InputSectionElem, Signal<Option<String>>)>,
+    > = Signal::new(HashMap::new());
+    // Groups of inputs are kept separately, along with the state of each of their entries
+    #[allow(clippy::type_complexity)]
+    let groups: Signal<HashMap<String, (InputGroup, Signal<Vec<GroupEntryState>>)>> =
         Signal::new(HashMap::new());
 
     let ctx = use_context::<WorkflowCtx>();
@@ -161,17 +765,19 @@ fn render_section(
             .iter()
             .map(cloned!(ctx => move |section_elem| {
                 let rendered = match section_elem {
-                    SectionElem::Text(text) => {
+                    // Redirects are followed before the user ever gets to this section, so they never need to be rendered
+                    SectionElem::Redirect { .. } => return View::empty(),
+                    SectionElem::Text(text) | SectionElem::ConditionalText { text, .. } => {
                         let text = parse_md_to_html(text);
                         view! {
                             div(class = "markdown", dangerously_set_inner_html = &text) {}
                         }
                     },
-                    SectionElem::Progression { text, link, tags } => {
+                    SectionElem::Progression { text, link, tags, .. } => {
                         let text = text.to_string();
                         let link = link.to_string();
                         let new_tags = tags.clone();
-                        let progression_handler = cloned!(ctx, form_values, name => move |_| {
+                        let progression_handler = cloned!(ctx, form_values, groups, name, input_err_msg => move |_| {
                             // If the user selects this progression, we need to set the new location and update the tags
                             let history = (*ctx.history.get()).clone();
                             let mut tags = Vec::new(); // This is for just the tags accumulated in this section
@@ -179,37 +785,23 @@ fn render_section(
                             // All the form values for this section should be sent to the global store for later inteprolation
                             let form_values_global = (*ctx.form_values.get()).clone();
                             let mut do_change = true;
-                            for (_id, (value_signal, input, show_err)) in form_values.get().iter() {
-                                let value = (*value_signal.get()).clone();
-                                if value.is_empty() && !input.optional {
-                                    show_err.clone().set(true);
+                            for (_id, (value, input, err)) in form_values.get().iter() {
+                                if !submit_input(&ctx, input, value, err, &[], &input_err_msg, &mut tags) {
                                     do_change = false;
-                                } else {
-                                    show_err.clone().set(false);
-                                    // If this is a select input, some of its options might want to add tags if they've been selected
-                                    if let Input::Select { options, .. } = input.input.clone() {
-                                        // We need to create a map of simple option text names to the tags they might add
-                                        let options_simple: HashMap<String, Vec<String>> = options.iter().map(|opt| match opt {
-                                            SelectOption::Simple(text) => (text.to_string(), vec![]),
-                                            SelectOption::WithTags { text, tags } => (text.to_string(), tags.clone())
-                                        }).collect();
-                                        let values_vec = value.split(", "); // This is the reason we don't allow commas in select options!
-                                        for selected_value in values_vec {
-                                            let tags_to_add = match options_simple.get(selected_value) {
-                                                Some(tags) => tags,
-                                                // This is impossible because we're indexing based on options the user only typed in one place
-                                                None => unreachable!()
-                                            }.clone();
-                                            tags.extend(tags_to_add);
-                                        }
-                                    }
-                                    // Do the same for boolean inputs
-                                    if let Input::Text { input_type: InputType::Boolean { tags: Some(new_tags) } } = input.input.clone() {
-                                        if value == "true" {
-                                            tags.extend(new_tags);
+                                }
+                            }
+                            // Every entry in a group is checked just like a normal input
+                            for (_id, (group, entries)) in groups.get().iter() {
+                                let shown = group.when.as_ref().is_none_or(|when| ctx.eval(when));
+                                for entry in entries.get().iter() {
+                                    let values = entry_values(&group.inputs, entry);
+                                    for (input, (value, err)) in group.inputs.iter().zip(entry.iter()) {
+                                        if !shown {
+                                            err.clone().set(None);
+                                        } else if !submit_input(&ctx, input, value, err, &values, &input_err_msg, &mut tags) {
+                                            do_change = false;
                                         }
                                     }
-                                    // The value has already been registered globally, so we don't need to do any more
                                 }
                             }
                             if do_change {
@@ -227,6 +819,15 @@ fn render_section(
                                     .map(|(_, v)| v)
                                     .cloned()
                                     .collect();
+                                // If where we're going redirects straight away, we skip over it (and its tags count as coming from this section)
+                                let mut all_tags: Vec<String> = history
+                                    .iter()
+                                    .take(history_pos)
+                                    .flat_map(|res| res.tags.iter().cloned())
+                                    .collect();
+                                all_tags.extend(tags.iter().cloned());
+                                let (link, redirect_tags) = ctx.resolve_redirects(link.clone(), &all_tags);
+                                tags.extend(redirect_tags);
                                 // Update the history for this section with the tags we've accumulated
                                 history[history_pos] = SectionResult {
                                     name: name.clone(),
@@ -241,7 +842,9 @@ fn render_section(
                                 ctx.history_pos.set(history_pos + 1);
                                 ctx.history.set(history);
                                 // This reactively updates the section being displayed to the user (though we can do more stuff after this if we want)
-                                ctx.loc.set(link.clone());
+                                ctx.loc.set(link);
+                                // The browser's back button should bring the user back here (this also throws away any forward entries, just like we did)
+                                push_browser_entry(history_pos + 1);
                             }
                         });
                         view! {
@@ -256,256 +859,79 @@ fn render_section(
                             }
                         }
                     },
-                    SectionElem::Input(input_props @ InputSectionElem { id, default, label, input, optional }) => {
+                    SectionElem::Input(input_props @ InputSectionElem { id, .. }) => {
                         // If we've moved back through the history, there may be records for this input (which we should autofill)
                         let mut form_values_map = (*form_values.get()).clone();
-                        let show_err = Signal::new(false);
+                        let err = Signal::new(None);
                         let mut form_values_global = (*ctx.form_values.get()).clone();
                         let input_value = if form_values_global.contains_key(id) {
                             Signal::new((*form_values_global.get(id).unwrap().get()).clone())
                         } else {
-                            Signal::new(String::new())
+                            Signal::new(FormValue::from_input(input_props))
                         };
                         // Register the value locally (so that progression elements can play with it)
-                        form_values_map.insert(id.to_string(), (input_value.clone(), input_props.clone(), show_err.clone()));
+                        form_values_map.insert(id.to_string(), (input_value.clone(), input_props.clone(), err.clone()));
                         form_values.set(form_values_map);
                         // Register the value globally (that way values are still saved even if the user moves back in history before submitting this section)
                         form_values_global.insert(id.to_string(), input_value.clone());
                         ctx.form_values.set(form_values_global);
 
-                        // If we have pre-existing data, we'll override the default
-                        let default = if !input_value.get().is_empty() {
-                            (*input_value.get()).clone()
-                        } else {
-                            default.clone().unwrap_or_else(|| "".to_string())
-                        };
-                        // Without this, the default values don't actually do anything
-                        // We still set them with `value` though for progressive enhancement and accessibility
-                        input_value.set(default.clone());
-
-                        let id = id.to_string();
-                        let id_for_err_label = id.clone();
-                        let label = label.clone();
-
-                        let err_label = create_memo(cloned!(show_err, input_err_msg, id_for_err_label => move || {
-                            if *show_err.get() {
-                                let id_for_err_label = id_for_err_label.clone();
-                                view! {
-                                    label(for = id_for_err_label) { (input_err_msg) }
-                                }
-                            } else {
-                                View::empty()
-                            }
-                        }));
-
-                        // We render the asterisk for required values based on a class
-                        let label_class = if !optional {
-                            "input-required"
-                        } else {
-                            ""
-                        };
-
-                        let input_rendered = match input {
-                            Input::Text { input_type } => {
-                                // We make all the placeholders empty because that allows the CSS `:placeholder-shown` selector to work
-                                match input_type {
-                                    // Multiline inputs use a `textarea` rather than an `input`, so we split off here
-                                    InputType::Multiline => view! {
-                                        // We want to keep the integrity of the page, so it's only resizeable in the y-direction
-                                        label(class = "custom-input") {
-                                            textarea(bind:value = input_value, class = "resize-y", placeholder = "") { (default) }
-                                            span(class = label_class) { (label) }
-                                        }
-                                        ((*err_label.get()).clone())
-                                    },
-                                    // A lot of the other input types are the same (other than their `type`), except for the ones with extra properties
-                                    InputType::Number { min, max } => match (*min, *max) {
-                                        (Some(min), Some(max)) => view! {
-                                            label(class = "custom-input") {
-                                                input(bind:value = input_value, type = "number", min = min, max = max, value = default, id = id, placeholder = "") {}
-                                                span(class = label_class) { (label) }
-                                            }
-                                            ((*err_label.get()).clone())
-                                        },
-                                        (Some(min), None) => view! {
-                                            label(class = "custom-input") {
-                                                input(bind:value = input_value, type = "number", min = min, value = default, id = id, placeholder = "") {}
-                                                span(class = label_class) { (label) }
-                                            }
-                                            ((*err_label.get()).clone())
-                                        },
-                                        (None, Some(max)) => view! {
-                                            label(class = "custom-input") {
-                                                input(bind:value = input_value, type = "number", max = max, value = default, id = id, placeholder = "") {}
-                                                span(class = label_class) { (label) }
-                                            }
-                                            ((*err_label.get()).clone())
-                                        },
-                                        (None, None) => view! {
-                                            label(class = "custom-input") {
-                                                input(bind:value = input_value, type = "number", value = default, id = id, placeholder = "") {}
-                                                span(class = label_class) { (label) }
-                                            }
-                                            ((*err_label.get()).clone())
-                                        },
-                                    },
-                                    InputType::Range { min, max } => {
-                                        let min = *min;
-                                        let max = *max;
-                                        view! {
-                                            // For a range, the min/max are mandatory
-                                            label(class = "custom-input") {
-                                                input(bind:value = input_value, type = "range", min = min, max = max, value = default, id = id, placeholder = "") {}
-                                                span(class = label_class) { (label) }
-                                            }
-                                            ((*err_label.get()).clone())
-                                        }
-                                    },
-                                    InputType::Boolean { .. } => {
-                                        let checked = Signal::new(
-                                            match default.as_str() {
-                                                "true" => true,
-                                                // Anything other than `true` is treated as `false`
-                                                // Additionally, we directly normalize it as such (in case the user leaves it as false)
-                                                _ => {
-                                                    input_value.set("false".to_string());
-                                                    false
-                                                }
-                                            }
-                                        );
-                                        // Based on that boolean state, we update the global string state
-                                        create_effect(cloned!(input_value, checked => move || {
-                                            let bool_val = *checked.get();
-                                            input_value.set(bool_val.to_string());
-                                        }));
-                                        view! {
-                                            label(class = "switch") {
-                                                span(class = label_class) { (label) }
-                                                // We need to move the keyboard accessibility from the `input` to the `span` that will actually hold the switch
-                                                input(type = "checkbox", bind:checked = checked.clone(), id = id, tabindex = "-1") {}
-                                                span(role = "checkButton", tabindex = "0", on:keydown = cloned!(checked => move |ev: web_sys::Event| {
-                                                    let ev: web_sys::KeyboardEvent = ev.unchecked_into();
-                                                    // If this is the Enter key, we should toggle the state
-                                                    if ev.key_code() == 13 {
-                                                        checked.set(!*checked.get());
-                                                    }
-                                                })) {}
-                                            }
-                                            ((*err_label.get()).clone())
-                                        }
-                                   }
-                                    _ => {
-                                        let input_type = input_type.to_string();
-                                        view! {
-                                            label(class = "custom-input") {
-                                                input(bind:value = input_value, type = input_type, value = default, id = id, placeholder = "") {}
-                                                span(class = label_class) { (label) }
-                                            }
-                                            ((*err_label.get()).clone())
-                                        }
-                                    }
-                                }
-                            },
-                            Input::Select { options, can_select_multiple } => {
-                                let default_opts: Vec<&str> = default.split(", ").collect();
-                                let opts_rendered = View::new_fragment(
-                                    options
+                        render_input(input_props, id.to_string(), input_value, err)
+                    }
+                    SectionElem::Group(group) => {
+                        let group = group.clone();
+                        let mut form_values_global = (*ctx.form_values.get()).clone();
+                        // If we've moved back through the history, there may be entries for this group (which we should autofill)
+                        let entries: Vec<GroupEntryState> = match form_values_global.get(&group.group).map(|value| (*value.get()).clone()) {
+                            Some(FormValue::Group(entries)) if !entries.is_empty() => entries
+                                .iter()
+                                .map(|entry| {
+                                    group.inputs
                                         .iter()
-                                        .map(|opt| match opt {
-                                            SelectOption::Simple(text) => {
-                                                let text = text.to_string();
-                                                let value = text.clone();
-                                                let is_selected = default_opts.contains(&value.as_str());
-                                                view! {
-                                                    option(value = value, selected = is_selected) { (text) }
-                                                }
-                                            },
-                                            SelectOption::WithTags { text, .. } => {
-                                                let text = text.to_string();
-                                                let value = text.clone();
-                                                let is_selected = default_opts.contains(&value.as_str());
-                                                view! {
-                                                    option(value = value, selected = is_selected) { (text) }
-                                                }
-                                            }
+                                        .map(|input| {
+                                            let value = entry.get(&input.id).cloned().unwrap_or_else(|| FormValue::from_input(input));
+                                            (Signal::new(value), Signal::new(None))
                                         })
                                         .collect()
-                                );
-
-                                let multi_select_handler = cloned!(input_value => move |ev: web_sys::Event| {
-                                    let el: web_sys::HtmlSelectElement = ev.target().unwrap().unchecked_into();
-                                    let selected_opts = el.selected_options();
-                                    let selected_opts = js_sys::Array::from(&selected_opts).to_vec(); // An `HtmlCollection` will always be iterable
-                                    let values: Vec<String> = selected_opts.iter().map(|opt| opt.clone().unchecked_into::<HtmlOptionElement>().value()).collect();
-                                    // We convert the vector into a comma-delimited list, which will be interpolated if necessary
-                                    let values_list = values.join(", ");
-                                    input_value.set(values_list);
-                                });
-
-                                // This is only used for single selects
-                                // If we don't have a default set, we should show a placeholder
-                                let show_placeholder = Signal::new(default.is_empty());
-                                let show_placeholder_class = create_memo(cloned!(show_placeholder => move || {
-                                    if *show_placeholder.get() {
-                                        "".to_string()
-                                    } else {
-                                        "no-placeholder".to_string()
-                                    }
-                                }));
-
-                                match can_select_multiple {
-                                    true => view! {
-                                        label(class = "custom-input") {
-                                            div(class = "select-wrapper select-multiple") {
-                                                select(on:input = multi_select_handler, multiple = true) {
-                                                    (opts_rendered)
-                                                }
-                                            }
-                                            span(class = label_class) { (label) }
-                                        }
-                                        ((*err_label.get()).clone())
-                                    },
-                                    false => view! {
-                                        label(class = "custom-input") {
-                                            div(class = format!(
-                                                "select-wrapper {}",
-                                                show_placeholder_class.get()
-                                            )) {
-                                                // When this is changed in any way, we make sure the placeholder is no longer shown (you can't go back to the empty option)
-                                                select(bind:value = input_value, on:change = cloned!(show_placeholder => move |_| {
-                                                    show_placeholder.set(false);
-                                                })) {
-                                                    // If we don't have a blank (for i18n) default option, the user would have to select another option and then reselect whatever the browser makes the default to select it (not good UX!)
-                                                    (if default.is_empty() {
-                                                        view! {
-                                                            option(value = "", selected = true, disabled = true) { "" }
-                                                        }
-                                                    } else {
-                                                        View::empty()
-                                                    })
-                                                    (opts_rendered)
-                                                }
-                                            }
-                                            span(class = label_class) { (label) }
-                                        }
-                                        ((*err_label.get()).clone())
-                                    }
-                                }
-                            },
+                                })
+                                .collect(),
+                            _ => (0..group.min).map(|_| new_group_entry(&group.inputs)).collect(),
                         };
-                        view! {
-                            div(class = "w-full text-left") {
-                                (input_rendered)
-                            }
-                        }
+                        let entries = Signal::new(entries);
+                        let group_value = Signal::new(FormValue::Group(Vec::new()));
+                        // Register the group locally (so that progression elements can check its entries)
+                        let mut groups_map = (*groups.get()).clone();
+                        groups_map.insert(group.group.to_string(), (group.clone(), entries.clone()));
+                        groups.set(groups_map);
+                        // Register its value globally, just like an input's
+                        form_values_global.insert(group.group.to_string(), group_value.clone());
+                        ctx.form_values.set(form_values_global);
+                        // The value of the group is kept up to date with the values in all its entries
+                        create_effect(cloned!(entries, group_value, group => move || {
+                            let value = entries
+                                .get()
+                                .iter()
+                                .map(|entry| GroupEntry(
+                                    group.inputs
+                                        .iter()
+                                        .zip(entry.iter())
+                                        .map(|(input, (value, _))| (input.id.to_string(), (*value.get()).clone()))
+                                        .collect()
+                                ))
+                                .collect();
+                            group_value.set(FormValue::Group(value));
+                        }));
+
+                        render_group(ctx.clone(), group, entries)
                     }
                 };
                 // We wrap that because there should be space between the elements in a section
-                view! {
+                let rendered = view! {
                     div(class = "my-2") {
                         (rendered)
                     }
-                }
+                };
+                show_when(section_elem.when().cloned(), cloned!(ctx => move |when| ctx.eval(when)), rendered)
             }))
             .collect()
     );
@@ -513,11 +939,633 @@ fn render_section(
     view! { (elems) }
 }
 
+/// Shows the given view only if the given condition (if there is one) is true, according to the given evaluator (usually
+/// [`WorkflowCtx::eval`]). Conditional elements are shown and hidden reactively, so they'll update as the user fills out inputs in the same
+/// section.
+fn show_when<G: GenericNode>(
+    when: Option<Condition>,
+    eval: impl Fn(&Condition) -> bool + 'static,
+    rendered: View<G>,
+) -> View<G> {
+    match when {
+        Some(when) => {
+            let shown = create_memo(move || {
+                if eval(&when) {
+                    rendered.clone()
+                } else {
+                    View::empty()
+                }
+            });
+            view! { ((*shown.get()).clone()) }
+        }
+        None => rendered,
+    }
+}
+
+/// The state of an entry in a group of inputs, which is the value and error message of each input in it (in the order of the group's
+/// inputs).
+type GroupEntryState = Vec<(Signal<FormValue>, Signal<Option<String>>)>;
+
+/// Pairs the IDs of the given inputs in a group with their values in the given entry, so that conditions on inputs in that entry can
+/// reference the others.
+fn entry_values(
+    inputs: &[InputSectionElem],
+    entry: &GroupEntryState,
+) -> Vec<(String, Signal<FormValue>)> {
+    inputs
+        .iter()
+        .zip(entry.iter())
+        .map(|(input, (value, _))| (input.id.clone(), value.clone()))
+        .collect()
+}
+
+/// Creates the state for a new entry in a group with the given inputs.
+fn new_group_entry(inputs: &[InputSectionElem]) -> GroupEntryState {
+    inputs
+        .iter()
+        .map(|input| (Signal::new(FormValue::from_input(input)), Signal::new(None)))
+        .collect()
+}
+
+/// Renders a group of inputs, with buttons for adding and removing entries (within the group's limits).
+fn render_group<G: Html>(
+    ctx: WorkflowCtx,
+    InputGroup {
+        group: group_id,
+        label,
+        inputs,
+        min,
+        max,
+        add_text,
+        remove_text,
+        ..
+    }: InputGroup,
+    entries: Signal<Vec<GroupEntryState>>,
+) -> View<G> {
+    let entries_rendered = create_memo(cloned!(ctx, entries, group_id, inputs => move || {
+        let entries_list = entries.get();
+        View::new_fragment(
+            entries_list
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| {
+                    // This only needs to re-render when entries are added or removed, not whenever the user types in them
+                    let values = entry_values(&inputs, entry);
+                    let inputs_rendered = untrack(|| View::new_fragment(
+                        inputs
+                            .iter()
+                            .zip(entry.iter())
+                            .map(|(input, (value, err))| {
+                                // Input IDs only have to be unique within an entry, so we qualify them with the group and the entry's position
+                                let dom_id = format!("{}-{}-{}", group_id, i, input.id);
+                                let rendered = render_input(input, dom_id, value.clone(), err.clone());
+                                // Conditions on inputs in a group can reference the other inputs in the same entry
+                                show_when(input.when.clone(), cloned!(ctx, values => move |when| ctx.eval_in_entry(when, &values)), rendered)
+                            })
+                            .collect()
+                    ));
+                    // Removing an entry can't take the group below its minimum
+                    let remove_button = if entries_list.len() > min {
+                        let remove_handler = cloned!(entries => move |_| {
+                            let mut new_entries = (*entries.get()).clone();
+                            new_entries.remove(i);
+                            entries.set(new_entries);
+                        });
+                        let remove_text = remove_text.clone();
+                        view! {
+                            button(type = "button", class = "group-button", on:click = remove_handler) { (remove_text) }
+                        }
+                    } else {
+                        View::empty()
+                    };
+                    view! {
+                        div(class = "group-entry") {
+                            (inputs_rendered)
+                            (remove_button)
+                        }
+                    }
+                })
+                .collect()
+        )
+    }));
+    // Adding an entry can't take the group above its maximum
+    let add_button = create_memo(cloned!(entries, inputs => move || {
+        if max.is_none_or(|max| entries.get().len() < max) {
+            let add_handler = cloned!(entries, inputs => move |_| {
+                let mut new_entries = (*entries.get()).clone();
+                new_entries.push(new_group_entry(&inputs));
+                entries.set(new_entries);
+            });
+            let add_text = add_text.clone();
+            view! {
+                button(type = "button", class = "group-button", on:click = add_handler) { (add_text) }
+            }
+        } else {
+            View::empty()
+        }
+    }));
+
+    view! {
+        fieldset(class = "input-group", id = group_id) {
+            legend { (label) }
+            ((*entries_rendered.get()).clone())
+            ((*add_button.get()).clone())
+        }
+    }
+}
+
+/// Checks the value of the given input when the user tries to move on from its section, setting its error message if it's invalid, and adding
+/// any tags it accumulates to the given list otherwise. This returns whether or not the value was valid. Inputs that are hidden by their
+/// conditions are never required, and they don't accumulate any tags. If the input is in an entry of a group, the values of that entry's
+/// inputs should be given (see [`WorkflowCtx::eval_in_entry`]).
+fn submit_input(
+    ctx: &WorkflowCtx,
+    input: &InputSectionElem,
+    value: &Signal<FormValue>,
+    err: &Signal<Option<String>>,
+    entry: &[(String, Signal<FormValue>)],
+    input_err_msg: &str,
+    tags: &mut Vec<String>,
+) -> bool {
+    if let Some(when) = &input.when {
+        if !ctx.eval_in_entry(when, entry) {
+            err.set(None);
+            return true;
+        }
+    }
+    let value = (*value.get()).clone();
+    // Validation rules only apply if there's a value
+    let err_msg = if value.is_empty() {
+        if input.optional {
+            None
+        } else {
+            Some(input_err_msg.to_string())
+        }
+    } else {
+        validate(&input.validate, &value.to_string()).map(|msg| msg.to_string())
+    };
+    if err_msg.is_some() {
+        err.set(err_msg);
+        return false;
+    }
+    err.set(None);
+    // If this input has options (a select, radio group, or checkbox group), some of them might want to add tags if they've been selected
+    if let Some(options) = input.input.options() {
+        for selected_value in value.items() {
+            // The value can only ever be made up of options the user only typed in one place
+            if let Some(opt) = options.iter().find(|opt| opt.text() == selected_value) {
+                tags.extend(opt.tags().iter().cloned());
+            }
+        }
+    }
+    // Do the same for boolean inputs
+    if let Input::Text {
+        input_type: InputType::Boolean {
+            tags: Some(new_tags),
+        },
+    } = &input.input
+    {
+        if value == FormValue::Boolean(true) {
+            tags.extend(new_tags.iter().cloned());
+        }
+    }
+    // And for any rules that match the value itself (e.g. a version number in a certain range)
+    tags.extend(matching_tags(&input.tag_rules, &value));
+    // The value has already been registered globally, so we don't need to do any more
+    true
+}
+
+/// Renders an input with the given value and error message, which are managed by whatever owns the input (a section or a group). The given
+/// ID is used for the input's element, so it must be unique on the page.
+fn render_input<G: Html>(
+    InputSectionElem {
+        default,
+        label,
+        description,
+        hint,
+        example,
+        input,
+        optional,
+        ..
+    }: &InputSectionElem,
+    dom_id: String,
+    input_value: Signal<FormValue>,
+    err: Signal<Option<String>>,
+) -> View<G> {
+    // If we have pre-existing data, we'll override the default
+    let value = match default {
+        Some(default) if input_value.get().is_empty() => FormValue::from_default(input, default),
+        _ => (*input_value.get()).clone(),
+    };
+    // Without this, the default values don't actually do anything
+    // We still set them with `value` though for progressive enhancement and accessibility
+    input_value.set(value.clone());
+    let default_opts = value.items();
+    let default = value.to_string();
+    // Everything except inputs that allow multiple options works with the raw text the browser gives us, which we parse
+    // into a value of the right type whenever it changes
+    let text_value = Signal::new(default.clone());
+    if !input.is_multiple() {
+        let input = input.clone();
+        create_effect(cloned!(input_value, text_value => move || {
+            input_value.set(FormValue::from_text(&input, &text_value.get()));
+        }));
+    }
+
+    let id = dom_id;
+    let id_for_err_label = id.clone();
+    let label = label.clone();
+    // Any help text goes underneath the input, and the input points to it so that screen readers will read it out too
+    let help_id = format!("{}-help", id);
+    let has_help = description.is_some() || hint.is_some() || example.is_some();
+    let help = render_help(description, hint, example, help_id.clone());
+    // Sycamore can't leave an attribute out, and `aria-describedby` mustn't point to help text that isn't there, so we set it ourselves on
+    // whatever these end up referencing once the input has been rendered (switches have two elements that can be focused)
+    let described_ref = NodeRef::new();
+    let switch_ref = NodeRef::new();
+    let described_refs = [described_ref.clone(), switch_ref.clone()];
+
+    // A `fieldset` can't be labelled, so the errors for groups of options are announced as alerts and referenced from the group instead
+    let is_option_group = matches!(
+        input,
+        Input::RadioGroup { .. } | Input::CheckboxGroup { .. }
+    );
+    let err_id = format!("{}-err", id);
+    let err_label = create_memo(cloned!(err, id_for_err_label, err_id => move || {
+        match (*err.get()).clone() {
+            Some(err_msg) if is_option_group => {
+                let err_id = err_id.clone();
+                view! {
+                    p(id = err_id, role = "alert", class = "option-group-err") { (err_msg) }
+                }
+            },
+            Some(err_msg) => {
+                let id_for_err_label = id_for_err_label.clone();
+                view! {
+                    label(for = id_for_err_label) { (err_msg) }
+                }
+            },
+            None => View::empty(),
+        }
+    }));
+
+    // We render the asterisk for required values based on a class
+    let label_class = if !optional { "input-required" } else { "" };
+
+    let input_rendered = match input {
+        Input::Text { input_type } => {
+            // We make all the placeholders empty because that allows the CSS `:placeholder-shown` selector to work
+            match input_type {
+                // Multiline inputs use a `textarea` rather than an `input`, so we split off here
+                InputType::Multiline => view! {
+                    // We want to keep the integrity of the page, so it's only resizeable in the y-direction
+                    label(class = "custom-input") {
+                        textarea(bind:value = text_value, class = "resize-y", placeholder = "", ref = described_ref) { (default) }
+                        span(class = label_class) { (label) }
+                    }
+                    ((*err_label.get()).clone())
+                },
+                // Code inputs are the same, but monospaced (and browsers shouldn't try to fix the spelling of a stack trace)
+                // Sycamore treats `spellcheck` as a boolean attribute and would leave it out when it's false (which enables spellchecking),
+                // so we use a different case to set it to the string `false` (attribute names are case-insensitive in HTML)
+                InputType::Code { .. } => view! {
+                    label(class = "custom-input") {
+                        textarea(bind:value = text_value, class = "resize-y code-input", placeholder = "", spellCheck = "false", ref = described_ref) { (default) }
+                        span(class = label_class) { (label) }
+                    }
+                    ((*err_label.get()).clone())
+                },
+                // A lot of the other input types are the same (other than their `type`), except for the ones with extra properties
+                InputType::Number { min, max } => match (*min, *max) {
+                    (Some(min), Some(max)) => view! {
+                        label(class = "custom-input") {
+                            input(bind:value = text_value, type = "number", min = min, max = max, value = default, id = id, placeholder = "", ref = described_ref) {}
+                            span(class = label_class) { (label) }
+                        }
+                        ((*err_label.get()).clone())
+                    },
+                    (Some(min), None) => view! {
+                        label(class = "custom-input") {
+                            input(bind:value = text_value, type = "number", min = min, value = default, id = id, placeholder = "", ref = described_ref) {}
+                            span(class = label_class) { (label) }
+                        }
+                        ((*err_label.get()).clone())
+                    },
+                    (None, Some(max)) => view! {
+                        label(class = "custom-input") {
+                            input(bind:value = text_value, type = "number", max = max, value = default, id = id, placeholder = "", ref = described_ref) {}
+                            span(class = label_class) { (label) }
+                        }
+                        ((*err_label.get()).clone())
+                    },
+                    (None, None) => view! {
+                        label(class = "custom-input") {
+                            input(bind:value = text_value, type = "number", value = default, id = id, placeholder = "", ref = described_ref) {}
+                            span(class = label_class) { (label) }
+                        }
+                        ((*err_label.get()).clone())
+                    },
+                },
+                InputType::Range { min, max } => {
+                    let min = *min;
+                    let max = *max;
+                    view! {
+                        // For a range, the min/max are mandatory
+                        label(class = "custom-input") {
+                            input(bind:value = text_value, type = "range", min = min, max = max, value = default, id = id, placeholder = "", ref = described_ref) {}
+                            span(class = label_class) { (label) }
+                        }
+                        ((*err_label.get()).clone())
+                    }
+                }
+                InputType::Boolean { .. } => {
+                    // Anything other than `true` has already been treated as `false`
+                    let checked = Signal::new(value == FormValue::Boolean(true));
+                    // Based on that boolean state, we update the raw text state
+                    create_effect(cloned!(text_value, checked => move || {
+                        let bool_val = *checked.get();
+                        text_value.set(bool_val.to_string());
+                    }));
+                    view! {
+                        label(class = "switch") {
+                            span(class = label_class) { (label) }
+                            // We need to move the keyboard accessibility from the `input` to the `span` that will actually hold the switch
+                            input(type = "checkbox", bind:checked = checked.clone(), id = id, tabindex = "-1", ref = described_ref) {}
+                            span(role = "checkButton", tabindex = "0", ref = switch_ref, on:keydown = cloned!(checked => move |ev: web_sys::Event| {
+                                let ev: web_sys::KeyboardEvent = ev.unchecked_into();
+                                // If this is the Enter key, we should toggle the state
+                                if ev.key_code() == 13 {
+                                    checked.set(!*checked.get());
+                                }
+                            })) {}
+                        }
+                        ((*err_label.get()).clone())
+                    }
+                }
+                _ => {
+                    let input_type = input_type.to_string();
+                    view! {
+                        label(class = "custom-input") {
+                            input(bind:value = text_value, type = input_type, value = default, id = id, placeholder = "", ref = described_ref) {}
+                            span(class = label_class) { (label) }
+                        }
+                        ((*err_label.get()).clone())
+                    }
+                }
+            }
+        }
+        Input::RadioGroup { radio: options } => {
+            let options = options.options();
+            let opts_rendered = View::new_fragment(
+                options
+                    .iter()
+                    .map(|opt| {
+                        let text = opt.text().to_string();
+                        let value = text.clone();
+                        let is_selected = default == text;
+                        let group_name = id.clone();
+                        let change_handler = cloned!(text_value, text => move |_| {
+                            text_value.set(text.clone());
+                        });
+                        view! {
+                            label {
+                                input(type = "radio", name = group_name, value = value, checked = is_selected, on:change = change_handler) {}
+                                span { (text) }
+                            }
+                        }
+                    })
+                    .collect()
+            );
+            let required = (!optional).to_string();
+
+            view! {
+                // Radio buttons that share a name already support arrow-key navigation within their group
+                fieldset(class = "option-group", id = id, role = "radiogroup", aria-required = required, ref = described_ref) {
+                    legend(class = label_class) { (label) }
+                    (opts_rendered)
+                }
+                ((*err_label.get()).clone())
+            }
+        }
+        Input::CheckboxGroup {
+            checkboxes: options,
+        } => {
+            let options = options.options();
+            // Each checkbox has its own state, and the value of the input is derived from all of them (in the order of the options)
+            let checked_opts: Vec<(String, Signal<bool>)> = options
+                .iter()
+                .map(|opt| {
+                    (
+                        opt.text().to_string(),
+                        Signal::new(default_opts.iter().any(|v| v == opt.text())),
+                    )
+                })
+                .collect();
+            create_effect(cloned!(input_value, checked_opts => move || {
+                let values: Vec<String> = checked_opts
+                    .iter()
+                    .filter(|(_, checked)| *checked.get())
+                    .map(|(text, _)| text.to_string())
+                    .collect();
+                input_value.set(FormValue::List(values));
+            }));
+            let opts_rendered = View::new_fragment(
+                checked_opts
+                    .iter()
+                    .map(|(text, checked)| {
+                        let text = text.to_string();
+                        let value = text.clone();
+                        view! {
+                            label {
+                                input(type = "checkbox", value = value, bind:checked = checked.clone()) {}
+                                span { (text) }
+                            }
+                        }
+                    })
+                    .collect()
+            );
+
+            view! {
+                fieldset(class = "option-group", id = id, ref = described_ref) {
+                    legend(class = label_class) { (label) }
+                    (opts_rendered)
+                }
+                ((*err_label.get()).clone())
+            }
+        }
+        Input::Select {
+            options,
+            can_select_multiple,
+        } => {
+            let options = options.options();
+            let opts_rendered = View::new_fragment(
+                options
+                    .iter()
+                    .map(|opt| match opt {
+                        SelectOption::Simple(text) => {
+                            let text = text.to_string();
+                            let value = text.clone();
+                            let is_selected = default_opts.contains(&value);
+                            view! {
+                                option(value = value, selected = is_selected) { (text) }
+                            }
+                        }
+                        SelectOption::WithTags { text, .. } => {
+                            let text = text.to_string();
+                            let value = text.clone();
+                            let is_selected = default_opts.contains(&value);
+                            view! {
+                                option(value = value, selected = is_selected) { (text) }
+                            }
+                        }
+                    })
+                    .collect(),
+            );
+
+            let multi_select_handler = cloned!(input_value => move |ev: web_sys::Event| {
+                let el: web_sys::HtmlSelectElement = ev.target().unwrap().unchecked_into();
+                let selected_opts = el.selected_options();
+                let selected_opts = js_sys::Array::from(&selected_opts).to_vec(); // An `HtmlCollection` will always be iterable
+                let values: Vec<String> = selected_opts.iter().map(|opt| opt.clone().unchecked_into::<HtmlOptionElement>().value()).collect();
+                input_value.set(FormValue::List(values));
+            });
+
+            // This is only used for single selects
+            // If we don't have a default set, we should show a placeholder
+            let show_placeholder = Signal::new(default.is_empty());
+            let show_placeholder_class = create_memo(cloned!(show_placeholder => move || {
+                if *show_placeholder.get() {
+                    "".to_string()
+                } else {
+                    "no-placeholder".to_string()
+                }
+            }));
+
+            match can_select_multiple {
+                true => view! {
+                    label(class = "custom-input") {
+                        div(class = "select-wrapper select-multiple") {
+                            select(on:input = multi_select_handler, multiple = true, ref = described_ref) {
+                                (opts_rendered)
+                            }
+                        }
+                        span(class = label_class) { (label) }
+                    }
+                    ((*err_label.get()).clone())
+                },
+                false => view! {
+                    label(class = "custom-input") {
+                        div(class = format!(
+                            "select-wrapper {}",
+                            show_placeholder_class.get()
+                        )) {
+                            // When this is changed in any way, we make sure the placeholder is no longer shown (you can't go back to the empty option)
+                            select(bind:value = text_value, ref = described_ref, on:change = cloned!(show_placeholder => move |_| {
+                                show_placeholder.set(false);
+                            })) {
+                                // If we don't have a blank (for i18n) default option, the user would have to select another option and then reselect whatever the browser makes the default to select it (not good UX!)
+                                (if default.is_empty() {
+                                    view! {
+                                        option(value = "", selected = true, disabled = true) { "" }
+                                    }
+                                } else {
+                                    View::empty()
+                                })
+                                (opts_rendered)
+                            }
+                        }
+                        span(class = label_class) { (label) }
+                    }
+                    ((*err_label.get()).clone())
+                },
+            }
+        }
+    };
+    create_effect(cloned!(err => move || {
+        let mut described_by = Vec::new();
+        if has_help {
+            described_by.push(help_id.clone());
+        }
+        if is_option_group && err.get().is_some() {
+            described_by.push(err_id.clone());
+        }
+        for node_ref in &described_refs {
+            if let Some(node) = node_ref.try_get::<G>() {
+                if described_by.is_empty() {
+                    node.remove_attribute("aria-describedby");
+                } else {
+                    node.set_attribute("aria-describedby", &described_by.join(" "));
+                }
+            }
+        }
+    }));
+
+    view! {
+        div(class = "w-full text-left") {
+            (input_rendered)
+            (help)
+        }
+    }
+}
+
+/// Renders the description, hint, and example of an input underneath it (if it has any of them), in an element with the given ID (which
+/// the input should reference with `aria-describedby`).
+fn render_help<G: GenericNode>(
+    description: &Option<String>,
+    hint: &Option<String>,
+    example: &Option<String>,
+    help_id: String,
+) -> View<G> {
+    if description.is_none() && hint.is_none() && example.is_none() {
+        return View::empty();
+    }
+    let description = match description {
+        Some(description) => {
+            let description = parse_md_to_html(description);
+            view! {
+                div(class = "markdown", dangerously_set_inner_html = &description) {}
+            }
+        }
+        None => View::empty(),
+    };
+    let hint = match hint.clone() {
+        Some(hint) => view! {
+            p(class = "input-hint") { (hint) }
+        },
+        None => View::empty(),
+    };
+    let example = match example.clone() {
+        Some(example) => view! {
+            p(class = "input-example") { (example) }
+        },
+        None => View::empty(),
+    };
+
+    view! {
+        div(id = help_id, class = "input-help") {
+            (description)
+            (hint)
+            (example)
+        }
+    }
+}
+
 struct RenderReportEndpointProps {
-    preamble: String,
-    text: String,
+    preamble: Template,
+    text: Template,
     dest_text: String,
-    dest_url: String,
+    dest_url: Option<Template>,
+    max_url_len: usize,
+    copied_msg: String,
+    copy_failed_msg: String,
+    github: Option<GithubDest>,
+    gitlab: Option<GitlabDest>,
+    gitea: Option<GiteaDest>,
+    submit: Option<ReportSubmit>,
+    fields: HashMap<String, Template>,
+    locale: String,
+    workflow_name: String,
+    endpoint_name: String,
 }
 
 /// Renders a report endpoint.
@@ -528,10 +1576,20 @@ fn render_report_endpoint(
         text,
         dest_text,
         dest_url,
+        max_url_len,
+        copied_msg,
+        copy_failed_msg,
+        github,
+        gitlab,
+        gitea,
+        submit,
+        fields,
+        locale,
+        workflow_name,
+        endpoint_name,
     }: RenderReportEndpointProps,
 ) -> View<G> {
     let ctx = use_context::<WorkflowCtx>();
-    let preamble = parse_md_to_html(&preamble);
     // Flatten the tags into one single vector
     let mut flattened_tags: Vec<String> = Vec::new();
     let history = ctx.history.get();
@@ -543,13 +1601,24 @@ fn render_report_endpoint(
     // We now encode that internal data with base64
     let encoded_tags = base64::encode(tags_str);
 
-    // Interpolate form values into the text
-    // Except in very specific cases, it's faster to do this by simply trying to interpolate all form values
-    let mut interpolated_text = text;
-    let form_values = ctx.form_values.get();
-    for (id, value) in form_values.iter() {
-        interpolated_text = interpolated_text.replace(&format!("${{{}}}", id), &value.get());
-    }
+    // Fill in the templates with the form values and tags, along with the computed fields (which the user never sees)
+    let input_values: HashMap<String, FormValue> = ctx
+        .form_values
+        .get()
+        .iter()
+        .map(|(id, value)| (id.to_string(), (*value.get()).clone()))
+        .collect();
+    // Receivers of submitted reports only get what the user filled out, so the fields and built-ins are kept separate
+    let mut form_values = input_values.clone();
+    add_fields(
+        &mut form_values,
+        &fields,
+        &locale,
+        &workflow_name,
+        &flattened_tags,
+    );
+    let preamble = parse_md_to_html(&preamble.render_md(&form_values, &flattened_tags));
+    let interpolated_text = text.render(&form_values, &flattened_tags);
     // Now collate everything together in one convenient block
     // We hide the tags away in internal details
     // WARNING: If anything ever changes here, we need to update `getRequestedLabels` in the bot
@@ -557,23 +1626,143 @@ fn render_report_endpoint(
         "{}\n\n<details>\n<summary>Tribble internal data</summary>\n\n{}\n\n</details>",
         interpolated_text, encoded_tags
     );
-    // Interpolate that into the destination URL if needed
-    let dest_url = dest_url.replace("%s", &urlencoding::encode(&report_text));
-
-    let copy_handler = cloned!(report_text => move |_| {
-        wasm_bindgen_futures::spawn_local(cloned!(report_text => async move {
-            // Write the text to the clipboard
-            let window = web_sys::window().unwrap();
-            let clipboard = window.navigator().clipboard().unwrap();
-            // We want to copy the tags as well
-            let promise = clipboard.write_text(&report_text);
-            let fut = wasm_bindgen_futures::JsFuture::from(promise);
-            match fut.await {
-                Ok(_) => (),
-                Err(_) => todo!("handle errors in pages")
+    // Issue trackers support labels natively, so we don't need to hide the tags in the body for the bot with those
+    // If the report would make the URL too long, we'll copy it to the clipboard instead and send a placeholder message in the URL
+    let dest_link = if let Some(github) = github {
+        Some(fit_url(
+            |body| github_url(&github, &form_values, body, &flattened_tags),
+            &interpolated_text,
+            &copied_msg,
+            github.max_url_len,
+        ))
+    } else if let Some(gitlab) = gitlab {
+        Some(fit_url(
+            |body| gitlab_url(&gitlab, &form_values, body, &flattened_tags),
+            &interpolated_text,
+            &copied_msg,
+            gitlab.max_url_len,
+        ))
+    } else if let Some(gitea) = gitea {
+        Some(fit_url(
+            |body| gitea_url(&gitea, &form_values, body, &flattened_tags),
+            &interpolated_text,
+            &copied_msg,
+            gitea.max_url_len,
+        ))
+    } else {
+        // Interpolate the report into the destination URL if needed
+        dest_url.map(|dest_url| {
+            fit_url(
+                |body| {
+                    dest_url
+                        .render_url(&form_values, &flattened_tags)
+                        .replace("%s", &urlencoding::encode(body))
+                },
+                &report_text,
+                &copied_msg,
+                max_url_len,
+            )
+        })
+    };
+
+    // If the report can be submitted directly, we'll render a button for that, which will show the progress of the submission
+    let submit_rendered = match submit {
+        Some(ReportSubmit {
+            url,
+            text: submit_text,
+            headers,
+            success_msg,
+            err_msg,
+        }) => {
+            let payload = Rc::new(ReportPayload {
+                workflow: workflow_name,
+                endpoint: endpoint_name,
+                text: interpolated_text.clone(),
+                tags: flattened_tags.clone(),
+                form_values: input_values,
+            });
+            let state = Signal::new(SubmitState::Idle);
+            let submit_handler = cloned!(state => move |_| {
+                // We don't want to submit the same report twice
+                if matches!(*state.get(), SubmitState::Loading | SubmitState::Success) {
+                    return;
+                }
+                state.set(SubmitState::Loading);
+                wasm_bindgen_futures::spawn_local(cloned!(state, url, headers, payload => async move {
+                    match submit_report(&url, &headers, &payload).await {
+                        Ok(()) => state.set(SubmitState::Success),
+                        Err(err) => {
+                            web_sys::console::error_1(&err.clone().into());
+                            state.set(SubmitState::Error(err));
+                        }
+                    }
+                }));
+            });
+            let success_msg = parse_md_to_html(&success_msg);
+            let submit_view = create_memo(cloned!(state => move || {
+                let submit_handler = submit_handler.clone();
+                let submit_text = submit_text.clone();
+                let success_msg = success_msg.clone();
+                match &*state.get() {
+                    SubmitState::Success => view! {
+                        div(class = "markdown mb-2", role = "status", dangerously_set_inner_html = &success_msg) {}
+                    },
+                    state => {
+                        let loading = *state == SubmitState::Loading;
+                        let err_view = match state {
+                            SubmitState::Error(_) => {
+                                let err_msg = err_msg.clone();
+                                view! {
+                                    p(class = "text-red-600 dark:text-red-400 text-sm mb-1", role = "alert") { (err_msg) }
+                                }
+                            },
+                            _ => View::empty(),
+                        };
+                        view! {
+                            (err_view)
+                            button(
+                                on:click = submit_handler,
+                                disabled = loading,
+                                aria-busy = loading.to_string(),
+                                class = "group inline-flex items-center p-5 mb-2 mr-2 text-lg shadow-md hover:shadow-lg dark:shadow-lg dark:hover:shadow-xl transition-shadow duration-200 rounded-lg disabled:opacity-50 disabled:cursor-wait"
+                            ) {
+                                (submit_text)
+                                div(class = "h-5 w-5 group-hover:ml-1 transition-all ease-in-out duration-200") {
+                                    (if loading {
+                                        svg!(r#"<svg aria-hidden=true xmlns="http://www.w3.org/2000/svg" class="animate-spin" fill="none" viewBox="0 0 24 24"><circle class="opacity-25" cx="12" cy="12" r="10" stroke="currentColor" stroke-width="4"></circle><path class="opacity-75" fill="currentColor" d="M4 12a8 8 0 018-8V0C5.373 0 0 5.373 0 12h4z"></path></svg>"#)
+                                    } else {
+                                        svg!(r#"<svg aria-hidden=true xmlns="http://www.w3.org/2000/svg" fill="none" viewBox="0 0 24 24" stroke="currentColor"><path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M12 19l9 2-9-18-9 18 9-2zm0 0v-8" /></svg>"#)
+                                    })
+                                }
+                            }
+                        }
+                    }
+                }
+            }));
+            view! {
+                div(aria-live = "polite") {
+                    ((*submit_view.get()).clone())
+                }
             }
-        }));
+        }
+        None => View::empty(),
+    };
+
+    // We want to copy the tags as well
+    let copy_state = Signal::new(CopyState::Idle);
+    let copy_handler = cloned!(report_text, copy_state => move |_| {
+        copy_to_clipboard(report_text.clone(), copy_state.clone())
     });
+    // The report is already shown in full, so the user can copy it from there if we can't
+    let copy_failed = create_memo(cloned!(copy_failed_msg => move || match *copy_state.get() {
+        CopyState::Failed(_) => {
+            let copy_failed_msg = copy_failed_msg.clone();
+            view! {
+                p(class = "text-sm mb-1", role = "alert") { (copy_failed_msg) }
+            }
+        }
+        _ => View::empty(),
+    }));
 
     view! {
         div(class = "markdown mb-2", dangerously_set_inner_html = &preamble) {}
@@ -591,22 +1780,123 @@ fn render_report_endpoint(
                 (report_text)
             }
         }
+        ((*copy_failed.get()).clone())
+        // This lets the user submit the report directly, if that's been configured
+        (submit_rendered)
         // This lets the user go to an external URL for reporting their issue
-        a(
-            href = dest_url,
-            // Even if it's internal to the site, this should never be handled by the router
-            // Tribble is a separate system, so unless it's been plugin-augmented, this will always be outside our control
-            rel = "external",
-            // The new link should always open in a new tab, to prevent any possible data loss (e.g. URL too long)
-            target = "_blank",
-            class = "group inline-flex items-center p-5 text-lg shadow-md hover:shadow-lg dark:shadow-lg dark:hover:shadow-xl transition-shadow duration-200 rounded-lg"
-        ) {
-            (dest_text)
-            div(class = "h-5 w-5 group-hover:ml-1 transition-all ease-in-out duration-200") {
-                (svg!(r#"<svg aria-hidden=true xmlns="http://www.w3.org/2000/svg" fill="none" viewBox="0 0 24 24" stroke="currentColor"><path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M9 5l7 7-7 7" /></svg>"#))
+        (match dest_link.clone() {
+            Some(dest_link) => {
+                let dest_text = dest_text.clone();
+                let (dest_url, copy_body) = match dest_link {
+                    DestLink::Full(url) => (url, None),
+                    DestLink::Shortened { url, body } => (url, Some(body)),
+                };
+                // If the report didn't fit in the URL, we'll tell the user, and copy it when they click the link (the new tab will still be opened)
+                let notice = match &copy_body {
+                    Some(_) => {
+                        let copied_msg = copied_msg.clone();
+                        view! {
+                            p(class = "text-sm mb-1", role = "note") { (copied_msg) }
+                        }
+                    },
+                    None => View::empty(),
+                };
+                // If we couldn't copy it, the user will have to do that themselves (this might not be the same as the report shown above)
+                let copy_state = Signal::new(CopyState::Idle);
+                let copy_failed = create_memo(cloned!(copy_state, copy_failed_msg => move || match &*copy_state.get() {
+                    CopyState::Failed(body) => {
+                        let body = body.clone();
+                        let copy_failed_msg = copy_failed_msg.clone();
+                        view! {
+                            p(class = "text-sm mb-1", role = "alert") { (copy_failed_msg) }
+                            textarea(readonly = true, class = "copy-fallback mb-2") { (body) }
+                        }
+                    },
+                    _ => View::empty(),
+                }));
+                let click_handler = move |_| {
+                    if let Some(body) = &copy_body {
+                        copy_to_clipboard(body.clone(), copy_state.clone());
+                    }
+                };
+                view! {
+                    (notice)
+                    ((*copy_failed.get()).clone())
+                    a(
+                        href = dest_url,
+                        on:click = click_handler,
+                        // Even if it's internal to the site, this should never be handled by the router
+                        // Tribble is a separate system, so unless it's been plugin-augmented, this will always be outside our control
+                        rel = "external",
+                        // The new link should always open in a new tab, to prevent any possible data loss (e.g. URL too long)
+                        target = "_blank",
+                        class = "group inline-flex items-center p-5 text-lg shadow-md hover:shadow-lg dark:shadow-lg dark:hover:shadow-xl transition-shadow duration-200 rounded-lg"
+                    ) {
+                        (dest_text)
+                        div(class = "h-5 w-5 group-hover:ml-1 transition-all ease-in-out duration-200") {
+                            (svg!(r#"<svg aria-hidden=true xmlns="http://www.w3.org/2000/svg" fill="none" viewBox="0 0 24 24" stroke="currentColor"><path stroke-linecap="round" stroke-linejoin="round" stroke-width="2" d="M9 5l7 7-7 7" /></svg>"#))
+                        }
+                    }
+                }
+            },
+            None => View::empty(),
+        })
+    }
+}
+
+/// Adds an entry for the given position in the user's history to the browser's history, so that the back button will bring them back to
+/// where they are now. Like a progression, this throws away any entries after the current one. This must only be called in the browser.
+fn push_browser_entry(pos: usize) {
+    if let Ok(history) = web_sys::window().unwrap().history() {
+        let _ = history.push_state(&JsValue::from_f64(pos as f64), "");
+    }
+}
+
+/// Makes the browser's current entry point to the given position in the user's history. This must only be called in the browser.
+fn replace_browser_entry(pos: usize) {
+    if let Ok(history) = web_sys::window().unwrap().history() {
+        let _ = history.replace_state(&JsValue::from_f64(pos as f64), "");
+    }
+}
+
+/// The state of copying something to the user's clipboard.
+#[derive(Clone, Debug, PartialEq)]
+enum CopyState {
+    /// Nothing has been copied yet.
+    Idle,
+    /// The text has been copied to the user's clipboard.
+    Copied,
+    /// The browser wouldn't let us copy the given text, so the user will have to copy it themselves.
+    Failed(String),
+}
+
+/// Copies the given text to the user's clipboard in the background, updating the given state once that's done.
+fn copy_to_clipboard(text: String, state: Signal<CopyState>) {
+    wasm_bindgen_futures::spawn_local(async move {
+        match write_clipboard(&text).await {
+            Ok(()) => state.set(CopyState::Copied),
+            Err(err) => {
+                web_sys::console::error_1(&err.into());
+                state.set(CopyState::Failed(text));
             }
         }
+    });
+}
+
+/// Writes the given text to the user's clipboard. This will fail if the site isn't served over HTTPS (the Clipboard API isn't available
+/// there at all) or if the user hasn't given permission.
+async fn write_clipboard(text: &str) -> Result<(), String> {
+    let clipboard = web_sys::window().unwrap().navigator().clipboard();
+    // The bindings say this is always there, but it's `undefined` on insecure origins
+    if clipboard.is_undefined() {
+        return Err(
+            "the clipboard isn't available (the site must be served over HTTPS)".to_string(),
+        );
     }
+    wasm_bindgen_futures::JsFuture::from(clipboard.write_text(text))
+        .await
+        .map(|_| ())
+        .map_err(|err| err.as_string().unwrap_or_else(|| format!("{:?}", err)))
 }
 
 /// A navigational breadcrumbs element that allows the user to step through their progress.
@@ -639,6 +1929,7 @@ fn history_breadcrumbs() -> View<G> {
                             ctx.history_pos.set(i);
                             // Update the location to be this section (again, it can't be an endpoint)
                             ctx.loc.set(name.clone());
+                            push_browser_entry(i);
                         });
 
                         // If this is the current item, it shouldn't be a link
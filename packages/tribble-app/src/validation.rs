@@ -0,0 +1,293 @@
+use crate::form::FormValue;
+use regex::Regex;
+#[cfg(feature = "schema")]
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// A rule that the value of an input must follow, along with the message to show the user if it doesn't. Rules are only checked if the
+/// input has a value (whether or not it needs one is controlled by `optional`), and they're checked in order, so the user will only see the
+/// message for the first rule their value breaks.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Validation {
+    /// The rule itself.
+    #[serde(flatten)]
+    pub rule: Rule,
+    /// The message to show the user if their value breaks this rule. This does not support Markdown.
+    pub msg: String,
+}
+
+/// The different rules an input's value can be checked against.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    /// A regular expression that the whole value must match (like the HTML `pattern` attribute, there's no need to add `^` and `$`).
+    Pattern(Pattern),
+    /// The minimum number of characters the value can have.
+    MinLength(usize),
+    /// The maximum number of characters the value can have.
+    MaxLength(usize),
+}
+impl Rule {
+    /// Checks if the given value follows this rule.
+    pub fn check(&self, value: &str) -> bool {
+        match self {
+            Self::Pattern(pattern) => pattern.regex.is_match(value),
+            Self::MinLength(min) => value.chars().count() >= *min,
+            Self::MaxLength(max) => value.chars().count() <= *max,
+        }
+    }
+}
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pattern(pattern) => write!(f, "pattern {:?}", pattern.src),
+            Self::MinLength(min) => write!(f, "min_length {}", min),
+            Self::MaxLength(max) => write!(f, "max_length {}", max),
+        }
+    }
+}
+
+/// Checks the given value against the given validation rules, returning the message of the first rule it breaks (if any).
+pub fn validate<'a>(rules: &'a [Validation], value: &str) -> Option<&'a str> {
+    rules
+        .iter()
+        .find(|validation| !validation.rule.check(value))
+        .map(|validation| validation.msg.as_str())
+}
+
+/// A rule that adds tags if the value of an input matches it (e.g. a version that starts with `0.` could add `V:pre-1.0`). Every rule
+/// that matches adds its tags, and empty values never match anything.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TagRule {
+    /// What the value has to match.
+    #[serde(flatten)]
+    pub rule: TagMatch,
+    /// The tags to accumulate if the value matches.
+    pub tags: Vec<String>,
+}
+
+/// The different things a value can be matched against to add tags.
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMatch {
+    /// A regular expression that the whole value must match (like in validation rules, there's no need to add `^` and `$`, but that also
+    /// means matching the start of a value needs a `.*` on the end, as in `0\..*`).
+    Pattern(Pattern),
+    /// A range that the value must be within (inclusive at both ends), which is only for number inputs. Either end can be left out.
+    Range {
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+    },
+}
+impl TagMatch {
+    /// Checks if the given value matches this rule.
+    pub fn matches(&self, value: &FormValue) -> bool {
+        if value.is_empty() {
+            return false;
+        }
+        match self {
+            Self::Pattern(pattern) => pattern.regex.is_match(&value.to_string()),
+            Self::Range { min, max } => match value.as_number() {
+                Some(num) => min.is_none_or(|min| num >= min) && max.is_none_or(|max| num <= max),
+                None => false,
+            },
+        }
+    }
+}
+impl fmt::Display for TagMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pattern(pattern) => write!(f, "pattern {:?}", pattern.src),
+            Self::Range { min, max } => {
+                let fmt_end =
+                    |end: &Option<f64>| end.map_or("..".to_string(), |end| end.to_string());
+                write!(f, "range {} to {}", fmt_end(min), fmt_end(max))
+            }
+        }
+    }
+}
+
+/// Gets the tags that the given value accumulates from the given tag rules, in order.
+pub fn matching_tags(rules: &[TagRule], value: &FormValue) -> Vec<String> {
+    rules
+        .iter()
+        .filter(|rule| rule.rule.matches(value))
+        .flat_map(|rule| rule.tags.iter().cloned())
+        .collect()
+}
+
+/// A regular expression for validating input values. These are compiled when the configuration is parsed, so invalid expressions will be
+/// caught before anything is built.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "String", into = "String")]
+pub struct Pattern {
+    /// The original expression (this is what we serialize).
+    src: String,
+    /// The compiled expression, which is anchored at both ends.
+    regex: Regex,
+}
+impl Pattern {
+    /// Compiles a new pattern from the given expression.
+    pub fn new(src: &str) -> Result<Self, regex::Error> {
+        // The whole value has to match, so we anchor the expression (it's grouped in case it has alternations)
+        let regex = Regex::new(&format!("^(?:{})$", src))?;
+        Ok(Self {
+            src: src.to_string(),
+            regex,
+        })
+    }
+}
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.src == other.src
+    }
+}
+impl TryFrom<String> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(src: String) -> Result<Self, Self::Error> {
+        Self::new(&src)
+    }
+}
+impl From<Pattern> for String {
+    fn from(pattern: Pattern) -> Self {
+        pattern.src
+    }
+}
+#[cfg(feature = "schema")]
+impl JsonSchema for Pattern {
+    fn schema_name() -> String {
+        "Pattern".to_string()
+    }
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(yaml: &str) -> Validation {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+    fn tag_rule(yaml: &str) -> TagRule {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+    fn single(text: &str) -> FormValue {
+        FormValue::Single(text.to_string())
+    }
+
+    #[test]
+    fn patterns_match_the_whole_value() {
+        let pattern = Rule::Pattern(Pattern::new(r"\d+").unwrap());
+        assert!(pattern.check("123"));
+        assert!(!pattern.check("v123"));
+        assert!(!pattern.check("123 "));
+        // Alternations are grouped, so they can't escape the anchors
+        let pattern = Rule::Pattern(Pattern::new("a|b").unwrap());
+        assert!(pattern.check("a"));
+        assert!(!pattern.check("ab"));
+        assert!(!pattern.check("xa"));
+    }
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        assert!(Pattern::new("(").is_err());
+        assert!(serde_yaml::from_str::<Validation>(r#"{ pattern: "(", msg: "x" }"#).is_err());
+    }
+    #[test]
+    fn lengths_count_characters() {
+        // Each of these is more than one byte
+        assert!(Rule::MaxLength(3).check("äöü"));
+        assert!(!Rule::MaxLength(2).check("äöü"));
+        assert!(Rule::MinLength(3).check("äöü"));
+        assert!(!Rule::MinLength(4).check("äöü"));
+    }
+    #[test]
+    fn first_broken_rule_wins() {
+        let rules = vec![
+            rule(r#"{ min_length: 3, msg: "too short" }"#),
+            rule(r#"{ pattern: "[a-z]+", msg: "lowercase only" }"#),
+        ];
+        assert_eq!(validate(&rules, "ab"), Some("too short"));
+        assert_eq!(validate(&rules, "A"), Some("too short"));
+        assert_eq!(validate(&rules, "ABC"), Some("lowercase only"));
+        assert_eq!(validate(&rules, "abc"), None);
+        assert_eq!(validate(&[], ""), None);
+    }
+    #[test]
+    fn tag_patterns_match_the_whole_value() {
+        let rule = tag_rule(r#"{ pattern: "0\\..*", tags: [ "V:pre-1.0" ] }"#);
+        assert!(rule.rule.matches(&single("0.4.2")));
+        assert!(!rule.rule.matches(&single("10.4.2")));
+        // Lists are matched as they're shown
+        let rule = tag_rule(r#"{ pattern: "a, b", tags: [] }"#);
+        assert!(rule
+            .rule
+            .matches(&FormValue::List(vec!["a".to_string(), "b".to_string()])));
+    }
+    #[test]
+    fn ranges_are_inclusive_and_open_ended() {
+        let rule = tag_rule("{ range: { min: 1, max: 5 }, tags: [] }");
+        assert!(rule.rule.matches(&FormValue::Number(Some(1.0))));
+        assert!(rule.rule.matches(&FormValue::Number(Some(5.0))));
+        assert!(!rule.rule.matches(&FormValue::Number(Some(5.5))));
+        assert!(rule.rule.matches(&single("3")));
+        assert!(!rule.rule.matches(&single("three")));
+        let rule = tag_rule("{ range: { min: 10 }, tags: [] }");
+        assert!(rule.rule.matches(&FormValue::Number(Some(1e9))));
+        assert!(!rule.rule.matches(&FormValue::Number(Some(9.9))));
+    }
+    #[test]
+    fn empty_values_never_match() {
+        let rule = tag_rule(r#"{ pattern: ".*", tags: [] }"#);
+        assert!(!rule.rule.matches(&single("")));
+        let rule = tag_rule("{ range: {}, tags: [] }");
+        assert!(!rule.rule.matches(&FormValue::Number(None)));
+    }
+    #[test]
+    fn every_matching_rule_adds_its_tags() {
+        let rules = vec![
+            tag_rule(r#"{ pattern: "0\\..*", tags: [ "V:pre-1.0" ] }"#),
+            tag_rule(r#"{ pattern: "1\\..*", tags: [ "V:1" ] }"#),
+            tag_rule(r#"{ pattern: ".*\\.0", tags: [ "V:minor", "V:dot-zero" ] }"#),
+        ];
+        assert_eq!(
+            matching_tags(&rules, &single("0.0")),
+            vec!["V:pre-1.0", "V:minor", "V:dot-zero"]
+        );
+        assert!(matching_tags(&rules, &single("2.1")).is_empty());
+    }
+    #[test]
+    fn tag_matches_are_displayed_for_messages() {
+        assert_eq!(
+            tag_rule("{ range: { min: 1.5 }, tags: [] }")
+                .rule
+                .to_string(),
+            "range 1.5 to .."
+        );
+        assert_eq!(
+            tag_rule(r#"{ pattern: "a|b", tags: [] }"#).rule.to_string(),
+            r#"pattern "a|b""#
+        );
+    }
+    #[test]
+    fn rules_are_displayed_for_comparison() {
+        assert_eq!(
+            rule(r#"{ pattern: "\\d+", msg: "x" }"#).rule.to_string(),
+            r#"pattern "\\d+""#
+        );
+        assert_eq!(
+            rule(r#"{ max_length: 5, msg: "x" }"#).rule.to_string(),
+            "max_length 5"
+        );
+    }
+}
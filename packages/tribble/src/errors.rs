@@ -20,6 +20,8 @@ pub enum Error {
     },
     #[error(transparent)]
     ServeError(#[from] ServeError),
+    #[error(transparent)]
+    CheckError(#[from] CheckError),
 }
 
 #[derive(Error, Debug)]
@@ -123,3 +125,11 @@ pub enum ServeError {
         source: tribble_app::errors::ParserError,
     },
 }
+#[derive(Error, Debug)]
+pub enum CheckError {
+    #[error("couldn't parse your tribble configuration")]
+    ParserError {
+        #[source]
+        source: tribble_app::errors::ParserError,
+    },
+}
@@ -69,8 +69,8 @@ async fn core(dir: PathBuf) -> Result<i32, Error> {
     let root_cfg_path = opts.config;
     // Set the `TRIBBLE_CONF` environment variable to what the user provided (used by the static exporting binary)
     env::set_var("TRIBBLE_CONF", &root_cfg_path);
-    // If we're not cleaning up artifacts, create them if needed and remove the `dist/` directory
-    if !matches!(opts.subcmd, Subcommand::Clean) {
+    // If we're not cleaning up artifacts or just checking the config, create them if needed and remove the `dist/` directory
+    if !matches!(opts.subcmd, Subcommand::Clean | Subcommand::Check) {
         prep(dir.clone())?;
         delete_dist_dir(dir.clone())?;
     }
@@ -123,18 +123,30 @@ async fn core(dir: PathBuf) -> Result<i32, Error> {
                 // Parse that to get any language files
                 let cfg = Config::new(&root_cfg_path)
                     .map_err(|err| ServeError::ParserError { source: err })?;
-                if let Config::Root { languages } = cfg {
+                if let Config::Root { languages, .. } = cfg {
                     for (_, lang_file_cfg_path) in languages {
                         watcher
                             .watch(&lang_file_cfg_path, RecursiveMode::Recursive)
                             .map_err(|err| ServeError::WatchFileFailed {
-                                filename: lang_file_cfg_path,
+                                filename: lang_file_cfg_path.clone(),
                                 source: err,
-                            })?
+                            })?;
                     }
                 }
+                // Options can be loaded from data files, which we need to watch too (inputs can start or stop using them while we're
+                // running, so we'll update these after every rebuild)
+                let mut watched_option_files = option_files(&root_cfg_path)
+                    .map_err(|err| ServeError::ParserError { source: err })?;
+                for option_file in &watched_option_files {
+                    watcher
+                        .watch(option_file, RecursiveMode::Recursive)
+                        .map_err(|err| ServeError::WatchFileFailed {
+                            filename: option_file.to_string_lossy().to_string(),
+                            source: err,
+                        })?;
+                }
 
-                let res: Result<i32, Error> = loop {
+                let res: Result<i32, Error> = 'watch: loop {
                     match rx.recv() {
                         Ok(
                             DebouncedEvent::Write(_)
@@ -147,7 +159,7 @@ async fn core(dir: PathBuf) -> Result<i32, Error> {
                             match delete_dist_dir(dir.clone()) {
                                 Ok(()) => (),
                                 // If we can't delete the build artifacts, we can't continue
-                                Err(err) => break Err(err.into()),
+                                Err(err) => break 'watch Err(err.into()),
                             };
                             // Regardless of the event type, rebuild the app
                             if !no_build {
@@ -169,6 +181,35 @@ async fn core(dir: PathBuf) -> Result<i32, Error> {
                                 let time = (build_finish_time - rebuild_start_time).as_millis();
                                 println!(" 🛠 Rebuilt Tribble instance in {}ms.", time);
                             }
+                            // The configuration might load options from different data files now
+                            match option_files(&root_cfg_path) {
+                                Ok(option_files) => {
+                                    for option_file in watched_option_files
+                                        .iter()
+                                        .filter(|file| !option_files.contains(file))
+                                    {
+                                        // This fails if the file has already been deleted, which is fine, because it's not used anymore
+                                        let _ = watcher.unwatch(option_file);
+                                    }
+                                    for option_file in option_files
+                                        .iter()
+                                        .filter(|file| !watched_option_files.contains(file))
+                                    {
+                                        if let Err(err) =
+                                            watcher.watch(option_file, RecursiveMode::Recursive)
+                                        {
+                                            break 'watch Err(ServeError::WatchFileFailed {
+                                                filename: option_file.to_string_lossy().to_string(),
+                                                source: err,
+                                            }
+                                            .into());
+                                        }
+                                    }
+                                    watched_option_files = option_files;
+                                }
+                                // The build will have caught this already if we're building, and otherwise the user will find out when they do
+                                Err(err) => eprintln!("{}", fmt_err(&err)),
+                            }
                             // The server doesn't need to restart, but we'll make sure the user knows it's updated
                             println!(
                                 " 🛰 Your Tribble instance is now available at <http://{}:{}>!",
@@ -179,12 +220,14 @@ async fn core(dir: PathBuf) -> Result<i32, Error> {
                         }
                         // We're only watching specific files, so a removal or renaming is fatal
                         Ok(DebouncedEvent::Remove(_) | DebouncedEvent::Rename(_, _)) => {
-                            println!("One of your Tribble configuration files has been removed or renamed, please re-run this command.");
+                            println!("One of your Tribble configuration or options files has been removed or renamed, please re-run this command.");
                             break Ok(1);
                         }
                         // Any of the other events are either impossible because we're only watching files or unecessary to watch (e.g. `NotifyWrite`)
                         Ok(_) => continue,
-                        Err(err) => break Err(ServeError::WatcherError { source: err }.into()),
+                        Err(err) => {
+                            break 'watch Err(ServeError::WatcherError { source: err }.into())
+                        }
                     }
                 };
                 return res;
@@ -202,6 +245,28 @@ async fn core(dir: PathBuf) -> Result<i32, Error> {
             delete_tribble_dir(dir)?;
             0
         }
+        Subcommand::Check => {
+            let problems = tribble_app::check::check_config(&root_cfg_path)
+                .map_err(|err| CheckError::ParserError { source: err })?;
+            if problems.is_empty() {
+                let finish_time = Instant::now();
+                let time = (finish_time - start_time).as_millis();
+                println!(
+                    " ✅ Checked Tribble configuration in {}ms, no problems found.",
+                    time
+                );
+                0
+            } else {
+                for problem in &problems {
+                    eprintln!("{}", problem);
+                }
+                eprintln!(
+                    " ❌ Found {} problem(s) in your Tribble configuration.",
+                    problems.len()
+                );
+                1
+            }
+        }
         Subcommand::Deploy { output, path } => {
             // Set the base path in Perseus based on `--path`
             env::set_var("PERSEUS_BASE_PATH", path);
@@ -231,3 +296,28 @@ async fn core(dir: PathBuf) -> Result<i32, Error> {
     };
     Ok(exit_code)
 }
+
+/// Gets the paths to all the data files that options are loaded from in the configuration at the given path (and all its language files,
+/// if it's a root file), so they can be watched for changes.
+fn option_files(root_cfg_path: &str) -> Result<Vec<PathBuf>, tribble_app::errors::ParserError> {
+    let lang_cfg_paths = match Config::new(root_cfg_path)? {
+        Config::Root { languages, .. } => languages.into_values().collect(),
+        Config::Language { .. } => vec![root_cfg_path.to_string()],
+    };
+    let mut option_files = Vec::new();
+    for lang_cfg_path in lang_cfg_paths {
+        if let Config::Language { workflows, .. } = Config::new(&lang_cfg_path)? {
+            for workflow in workflows.values() {
+                option_files.extend(tribble_app::option_files::option_files(
+                    &lang_cfg_path,
+                    workflow,
+                ));
+            }
+        }
+    }
+    // Different languages will usually share the same data files
+    option_files.sort();
+    option_files.dedup();
+
+    Ok(option_files)
+}
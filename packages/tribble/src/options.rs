@@ -47,4 +47,7 @@ pub enum Subcommand {
     },
     /// Deletes the `.tribble/` directory in the case of a corruption
     Clean,
+    /// Checks your Tribble configuration files for problems like broken links, missing endpoints, and references to inputs that
+    /// don't exist, exiting with a non-zero exit code if any are found
+    Check,
 }